@@ -0,0 +1,42 @@
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use mprovision::profile::{Info, Profile};
+use mprovision_cli::profile_formatters::{format_multiline, format_multiline_string};
+use std::io::{self, Write};
+
+/// Number of profiles formatted in every iteration.
+const PROFILES_COUNT: usize = 1000;
+
+fn format(c: &mut Criterion) {
+    let info = Info::from_xml_data(include_bytes!("../../lib/tests/test.xml")).unwrap();
+    let profiles = (0..PROFILES_COUNT)
+        .map(|i| Profile {
+            path: format!("{}.mobileprovision", i).into(),
+            info: info.clone(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = c.benchmark_group("format_multiline");
+    group.throughput(Throughput::Elements(PROFILES_COUNT as u64));
+    group.bench_function("writer", |b| {
+        b.iter(|| {
+            let mut sink = io::sink();
+            for profile in &profiles {
+                format_multiline(profile, 30, false, false, false, None, &mut sink).unwrap();
+                writeln!(&mut sink).unwrap();
+            }
+        })
+    });
+    group.bench_function("string", |b| {
+        b.iter(|| {
+            let mut sink = io::sink();
+            for profile in &profiles {
+                let multiline = format_multiline_string(profile, 30, false, false, false).unwrap();
+                writeln!(&mut sink, "{}", multiline).unwrap();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, format);
+criterion_main!(benches);
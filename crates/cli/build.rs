@@ -0,0 +1,21 @@
+//! Provides the build date as `VERGEN_BUILD_DATE` unless it's already set.
+//!
+//! The date is taken from `SOURCE_DATE_EPOCH` if it's set, so reproducible
+//! builds get the same date.
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=VERGEN_BUILD_DATE");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    if std::env::var_os("VERGEN_BUILD_DATE").is_none() {
+        let date = match std::env::var("SOURCE_DATE_EPOCH") {
+            Ok(epoch) => epoch
+                .trim()
+                .parse()
+                .ok()
+                .and_then(|epoch| time::OffsetDateTime::from_unix_timestamp(epoch).ok())
+                .unwrap_or_else(|| panic!("SOURCE_DATE_EPOCH isn't a unix timestamp: {}", epoch)),
+            Err(_) => time::OffsetDateTime::now_utc(),
+        };
+        println!("cargo:rustc-env=VERGEN_BUILD_DATE={}", date.date());
+    }
+}
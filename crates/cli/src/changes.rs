@@ -0,0 +1,153 @@
+//! Changes of provisioning profiles since a date.
+
+use mprovision::profile::Profile;
+use std::time::SystemTime;
+use time::Date;
+
+/// Represents changes of provisioning profiles since a date.
+#[derive(Debug, Default, PartialEq)]
+pub struct Changes<'a> {
+    /// Profiles created or modified since the date.
+    pub new: Vec<&'a Profile>,
+    /// Profiles that existed before the date and have a new profile with the same
+    /// app identifier, so they are most likely to be removed.
+    pub replaced: Vec<&'a Profile>,
+    /// Pairs of replaced and new profiles with different names.
+    pub renamed: Vec<(&'a Profile, &'a Profile)>,
+}
+
+impl<'a> Changes<'a> {
+    /// Finds changes of `profiles` given with modification times of their files
+    /// since a `since` time.
+    pub fn new(profiles: &'a [(Profile, SystemTime)], since: SystemTime) -> Self {
+        let (new, old): (Vec<_>, Vec<_>) = profiles.iter().partition(|(profile, modified)| {
+            profile.info.creation_date >= since || *modified >= since
+        });
+        let mut changes = Self {
+            new: new.iter().map(|(profile, _)| profile).collect(),
+            ..Self::default()
+        };
+        for (old_profile, _) in old {
+            let successor = changes
+                .new
+                .iter()
+                .filter(|profile| profile.info.app_identifier == old_profile.info.app_identifier)
+                .max_by_key(|profile| profile.info.creation_date);
+            if let Some(successor) = successor {
+                changes.replaced.push(old_profile);
+                if successor.info.name != old_profile.info.name {
+                    changes.renamed.push((old_profile, successor));
+                }
+            }
+        }
+        changes
+    }
+
+    /// Formats the changes as markdown, sections without changes are omitted.
+    pub fn to_markdown(&self, since: Date) -> String {
+        fn item(profile: &Profile) -> String {
+            format!(
+                "- `{}` {} ({})\n",
+                profile.info.uuid, profile.info.name, profile.info.app_identifier
+            )
+        }
+        let mut markdown = format!("## Provisioning profile changes since {}\n", since);
+        if self.new.is_empty() {
+            markdown.push_str("\nNo changes.\n");
+            return markdown;
+        }
+        markdown.push_str("\n### New profiles\n\n");
+        self.new
+            .iter()
+            .for_each(|profile| markdown.push_str(&item(profile)));
+        if !self.replaced.is_empty() {
+            markdown.push_str("\n### Replaced profiles\n\n");
+            self.replaced
+                .iter()
+                .for_each(|profile| markdown.push_str(&item(profile)));
+        }
+        if !self.renamed.is_empty() {
+            markdown.push_str("\n### Renamed profiles\n\n");
+            for (old, new) in &self.renamed {
+                markdown.push_str(&format!(
+                    "- `{}` {} → `{}` {}\n",
+                    old.info.uuid, old.info.name, new.info.uuid, new.info.name
+                ));
+            }
+        }
+        markdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mprovision::profile::Info;
+    use std::time::Duration;
+    use time::macros::date;
+
+    const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+    fn profile(uuid: &str, name: &str, app_identifier: &str, days: u32) -> Profile {
+        Profile {
+            path: format!("/profiles/{}.mobileprovision", uuid).into(),
+            info: Info {
+                uuid: uuid.into(),
+                name: name.into(),
+                app_identifier: app_identifier.into(),
+                creation_date: SystemTime::UNIX_EPOCH + DAY * days,
+                expiration_date: SystemTime::UNIX_EPOCH + DAY * (days + 365),
+                ..Info::empty()
+            },
+        }
+    }
+
+    #[test]
+    fn changes_since_date() {
+        let since = SystemTime::UNIX_EPOCH + DAY * 10;
+        let profiles = [
+            (profile("1", "App", "T.com.app", 1), since - DAY),
+            (profile("2", "Widget", "T.com.widget", 1), since - DAY),
+            (profile("3", "Other", "T.com.other", 1), since - DAY),
+            (profile("4", "App", "T.com.app", 11), since + DAY),
+            (profile("5", "New Widget", "T.com.widget", 12), since + DAY),
+            (profile("6", "Modified", "T.com.modified", 1), since + DAY),
+        ];
+        let changes = Changes::new(&profiles, since);
+        let uuids = |profiles: &[&Profile]| {
+            profiles
+                .iter()
+                .map(|profile| profile.info.uuid.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(uuids(&changes.new), ["4", "5", "6"]);
+        assert_eq!(uuids(&changes.replaced), ["1", "2"]);
+        assert_eq!(changes.renamed.len(), 1);
+        assert_eq!(changes.renamed[0].0.info.uuid, "2");
+        assert_eq!(changes.renamed[0].1.info.uuid, "5");
+        assert_eq!(
+            changes.to_markdown(date!(1970 - 01 - 11)),
+            "## Provisioning profile changes since 1970-01-11\n\
+             \n### New profiles\n\n\
+             - `4` App (T.com.app)\n\
+             - `5` New Widget (T.com.widget)\n\
+             - `6` Modified (T.com.modified)\n\
+             \n### Replaced profiles\n\n\
+             - `1` App (T.com.app)\n\
+             - `2` Widget (T.com.widget)\n\
+             \n### Renamed profiles\n\n\
+             - `2` Widget → `5` New Widget\n"
+        );
+    }
+
+    #[test]
+    fn no_changes() {
+        let profiles = [(profile("1", "App", "T.com.app", 1), SystemTime::UNIX_EPOCH)];
+        let changes = Changes::new(&profiles, SystemTime::UNIX_EPOCH + DAY * 10);
+        assert_eq!(changes, Changes::default());
+        assert_eq!(
+            changes.to_markdown(date!(1970 - 01 - 11)),
+            "## Provisioning profile changes since 1970-01-11\n\nNo changes.\n"
+        );
+    }
+}
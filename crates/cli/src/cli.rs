@@ -1,23 +1,62 @@
-use clap::Parser;
-use std::path::PathBuf;
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use mprovision::format::OutputFormat;
+use mprovision::profile::ProfileType;
+use std::path::{Path, PathBuf};
 use std::result;
+use std::time::SystemTime;
+use time::macros::format_description;
+use time::{Date, OffsetDateTime};
 
 /// A tool that helps iOS developers to manage mobileprovision files.
 #[derive(Debug, PartialEq, Parser)]
-#[command(author, about)]
+#[command(
+    name = "mprovision",
+    author,
+    about,
+    version,
+    arg_required_else_help = true,
+    after_help = "Exit status is 0 on success, 2 if no provisioning profiles are found or \
+                  arguments are invalid, and 1 on other errors."
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Print version as json
+    #[arg(long = "version-json", exclusive = true)]
+    pub version_json: bool,
+
+    /// Number of days before expiration when a profile is highlighted as expiring soon
+    #[arg(long = "warn-days", global = true, default_value_t = 30, value_parser = parse_days)]
+    pub warn_days: u64,
+
+    /// Whether to print additional information
+    #[arg(short = 'v', long = "verbose", global = true)]
+    pub verbose: bool,
+
+    /// Whether to suppress warnings
+    #[arg(short = 'q', long = "quiet", global = true, conflicts_with = "verbose")]
+    pub quiet: bool,
+}
+
+#[derive(Debug, PartialEq, Subcommand)]
 pub enum Command {
     /// Lists provisioning profiles
     #[command(name = "list")]
     List(ListParams),
 
-    /// Shows details of a provisioning profile using its uuid
+    /// Shows details of a provisioning profile using its uuid or file path
     #[command(name = "show")]
     ShowUuid(ShowUuidParams),
 
-    /// Shows details of a provisioning profile
+    /// Shows details of a provisioning profile, deprecated, use `show` instead
     #[command(name = "show-file")]
     ShowFile(ShowFileParams),
 
+    /// Shows changed fields between two provisioning profiles
+    #[command(name = "show-diff")]
+    ShowDiff(ShowDiffParams),
+
     /// Removes provisioning profiles
     #[command(name = "remove")]
     Remove(RemoveParams),
@@ -29,47 +68,256 @@ pub enum Command {
     /// Extracts provisioning profiles from ipa file or zip archive
     #[command(name = "extract")]
     Extract(ExtractParams),
+
+    /// Installs a provisioning profile from a file or a base64 string
+    #[command(name = "install")]
+    Install(InstallParams),
+
+    /// Validates provisioning profiles
+    #[command(name = "validate")]
+    Validate(ValidateParams),
+
+    /// Summarizes changes of provisioning profiles since a date as markdown
+    #[command(name = "changes")]
+    Changes(ChangesParams),
+
+    /// Backs up provisioning profiles into a tar.gz archive
+    #[command(name = "backup")]
+    Backup(BackupParams),
+
+    /// Restores provisioning profiles from a tar.gz archive made by `backup`
+    #[command(name = "restore")]
+    Restore(RestoreParams),
 }
 
 #[derive(Debug, Default, PartialEq, Parser)]
 pub struct ListParams {
-    /// Lists provisioning profiles that contain this text
-    #[arg(short = 't', long = "text", value_parser = clap::builder::NonEmptyStringValueParser::new())]
-    pub text: Option<String>,
+    /// Lists provisioning profiles that contain any of these texts
+    #[arg(short = 't', long = "text", num_args(0..), value_parser = clap::builder::NonEmptyStringValueParser::new())]
+    pub text: Vec<String>,
 
     /// Lists provisioning profiles that will expire in days
     #[arg(short = 'd', long = "expire-in-days", value_parser = parse_days)]
     pub expire_in_days: Option<u64>,
 
-    /// A directory where to search provisioning profiles
-    #[arg(long = "source")]
-    pub directory: Option<PathBuf>,
+    /// Whether to skip expired provisioning profiles
+    #[arg(long = "no-expired")]
+    pub no_expired: bool,
+
+    /// Directories where to search provisioning profiles, separated by ':' or
+    /// given by repeating the flag
+    #[arg(long = "source", value_delimiter = ':')]
+    pub directories: Vec<PathBuf>,
 
-    /// Output profile details in one line
+    /// Number of provisioning profiles to read at the same time, 0 means all cores
+    #[arg(short = 'j', long = "jobs", default_value_t = 0)]
+    pub jobs: usize,
+
+    /// Output format: multiline, oneline, json, csv or table
+    #[arg(long = "format", default_value_t, value_parser = parse_format, conflicts_with_all = ["oneline", "json"])]
+    pub format: OutputFormat,
+
+    /// Deprecated, use `--format oneline` instead
     #[arg(long = "oneline")]
     pub oneline: bool,
+
+    /// Deprecated, use `--format json` instead
+    #[arg(long = "json", conflicts_with = "oneline")]
+    pub json: bool,
+
+    /// A field to sort provisioning profiles by [default: creation-date]
+    ///
+    /// The `MPROVISION_SORT_BY` environment variable takes precedence over this option.
+    #[arg(long = "sort-by", value_enum)]
+    pub sort_by: Option<SortBy>,
+
+    /// Output dates in the local timezone instead of UTC
+    #[arg(long = "local-time")]
+    pub local_time: bool,
+
+    /// Output expiration dates relative to now, e.g. "expires in 3 days"
+    #[arg(long = "relative-dates", conflicts_with = "local_time")]
+    pub relative_dates: bool,
+
+    /// Output only the number of provisioning profiles
+    #[arg(short = 'c', long = "count")]
+    pub count: bool,
+
+    /// Output only provisioning profiles after the one with this uuid, e.g. the
+    /// last one of a previous page
+    #[arg(long = "after", value_parser = clap::builder::NonEmptyStringValueParser::new())]
+    pub after: Option<String>,
+
+    /// Output at most this number of provisioning profiles
+    #[arg(long = "limit")]
+    pub limit: Option<usize>,
+
+    /// Whether to list macOS provisioning profiles (`*.provisionprofile`) as well
+    #[arg(long = "mac")]
+    pub mac: bool,
+
+    /// Truncate names of provisioning profiles to fit into this number of columns
+    /// [default: the width of the terminal]
+    #[arg(long = "width", value_parser = clap::value_parser!(u16).range(1..))]
+    pub width: Option<u16>,
+
+    /// Whether to print the number of read provisioning profiles to stderr
+    #[arg(long = "progress")]
+    pub progress: bool,
+
+    /// Whether to group provisioning profiles by their creation date
+    #[arg(long = "group-by-date", conflicts_with_all = ["count", "json"])]
+    pub group_by_date: bool,
+
+    /// Lists provisioning profiles for this platform
+    #[arg(long = "platform", value_parser = ["ios", "macos", "tvos", "watchos"])]
+    pub platform: Option<String>,
+}
+
+impl ListParams {
+    /// Returns an output format taking into account deprecated `--oneline` and
+    /// `--json` flags.
+    pub fn output_format(&self) -> OutputFormat {
+        if self.oneline {
+            OutputFormat::Oneline
+        } else if self.json {
+            OutputFormat::Json
+        } else {
+            self.format
+        }
+    }
+}
+
+/// Represents a field to sort provisioning profiles by.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, ValueEnum)]
+pub enum SortBy {
+    #[default]
+    CreationDate,
+    ExpirationDate,
+    Name,
+    Uuid,
+}
+
+impl SortBy {
+    /// Name of the environment variable that overrides the `--sort-by` option.
+    pub const ENV: &'static str = "MPROVISION_SORT_BY";
+
+    /// Parses a field to sort by the same way as the `--sort-by` option.
+    pub fn parse(s: &str) -> result::Result<Self, String> {
+        <Self as ValueEnum>::from_str(s, false)
+    }
 }
 
 #[derive(Debug, Default, PartialEq, Parser)]
 pub struct ShowUuidParams {
-    /// An uuid of a provisioning profile
-    #[arg(value_parser = clap::builder::NonEmptyStringValueParser::new())]
-    pub uuid: String,
+    /// An uuid or a file path of a provisioning profile
+    #[arg(
+        value_parser = clap::builder::NonEmptyStringValueParser::new(),
+        required_unless_present = "url"
+    )]
+    pub uuid: Option<String>,
 
     /// A directory where to search provisioning profiles
     #[arg(long = "source")]
     pub directory: Option<PathBuf>,
+
+    /// Number of provisioning profiles to read at the same time, 0 means all cores
+    #[arg(short = 'j', long = "jobs", default_value_t = 0)]
+    pub jobs: usize,
+
+    /// An HTTP(S) url to download a provisioning profile from, requires the `http` feature
+    #[arg(long = "url", conflicts_with_all = ["uuid", "directory"])]
+    pub url: Option<String>,
+}
+
+/// Represents an argument of the `show` command.
+#[derive(Debug, PartialEq)]
+pub enum ShowArg {
+    /// An uuid of a provisioning profile to search for.
+    Uuid(String),
+    /// A file path of a provisioning profile.
+    FilePath(PathBuf),
+}
+
+/// Detects whether an argument of the `show` command is an uuid or a file path.
+///
+/// An argument that isn't an uuid is a file path if it contains `/` or has the
+/// `mobileprovision` or `provisionprofile` extension, otherwise it's treated as
+/// an uuid.
+pub fn detect_show_arg(s: &str) -> ShowArg {
+    let is_uuid = s.len() == 36
+        && s.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    if !is_uuid && (s.contains('/') || mprovision::is_any_provision_profile(Path::new(s))) {
+        ShowArg::FilePath(PathBuf::from(s))
+    } else {
+        ShowArg::Uuid(s.to_owned())
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Parser)]
+pub struct ShowDiffParams {
+    /// A file path of an old provisioning profile
+    pub old_file: PathBuf,
+
+    /// A file path of a new provisioning profile
+    pub new_file: PathBuf,
+
+    /// Whether to print unchanged fields too
+    #[arg(long = "context")]
+    pub context: bool,
 }
 
 #[derive(Debug, Default, PartialEq, Parser)]
 pub struct ShowFileParams {
-    /// A file path of a provisioning profile
+    /// A file path of a provisioning profile, `-` to read it from stdin
     pub file: PathBuf,
+
+    /// Whether to print expiration dates of embedded developer certificates
+    #[arg(long = "cert-info")]
+    pub cert_info: bool,
+
+    /// Whether to decode base64 data values as text or hex for readability
+    #[arg(long = "decode-base64")]
+    pub decode_base64: bool,
+
+    /// Whether to indent the xml output
+    #[arg(long = "pretty")]
+    pub pretty: bool,
+
+    /// Highlights occurrences of a term in the output ignoring case
+    #[arg(long = "highlight", value_parser = clap::builder::NonEmptyStringValueParser::new())]
+    pub highlight: Option<String>,
+
+    /// Whether to print the file as is, e.g. to pass it to a code signing tool
+    #[arg(long = "raw", conflicts_with_all = ["cert_info", "decode_base64", "pretty", "highlight"])]
+    pub raw: bool,
+
+    /// Whether to print the file encoded as base64, e.g. to store it as a secret
+    #[arg(long = "base64", conflicts_with_all = ["cert_info", "decode_base64", "pretty", "highlight", "raw"])]
+    pub base64: bool,
+}
+
+#[derive(Debug, Default, PartialEq, Parser)]
+pub struct InstallParams {
+    /// A file path of a provisioning profile
+    #[arg(required_unless_present = "base64", conflicts_with = "base64")]
+    pub file: Option<PathBuf>,
+
+    /// Whether to read a base64 encoded provisioning profile from stdin
+    #[arg(long = "base64")]
+    pub base64: bool,
+
+    /// A directory where to install the provisioning profile
+    #[arg(long = "destination")]
+    pub destination: Option<PathBuf>,
 }
 
 #[derive(Debug, Default, PartialEq, Parser)]
 pub struct RemoveParams {
-    /// uuid(s) or bundle id(s) of provisioning profiles
+    /// uuid(s), bundle id(s) or name(s) of provisioning profiles
     #[arg(num_args(1..), value_parser = clap::builder::NonEmptyStringValueParser::new())]
     pub ids: Vec<String>,
 
@@ -77,9 +325,25 @@ pub struct RemoveParams {
     #[arg(long = "source")]
     pub directory: Option<PathBuf>,
 
+    /// Number of provisioning profiles to read at the same time, 0 means all cores
+    #[arg(short = 'j', long = "jobs", default_value_t = 0)]
+    pub jobs: usize,
+
     /// Whether to remove provisioning profiles permanently
     #[arg(long = "permanently")]
     pub permanently: bool,
+
+    /// Whether to treat ids containing `*` or `?` as glob patterns for bundle ids
+    #[arg(long = "glob")]
+    pub glob: bool,
+
+    /// Whether to fail without removing anything if any id has no provisioning profile
+    #[arg(
+        long = "fail-on-missing",
+        alias = "strict-ids",
+        conflicts_with = "glob"
+    )]
+    pub fail_on_missing: bool,
 }
 
 #[derive(Debug, Default, PartialEq, Parser)]
@@ -88,6 +352,23 @@ pub struct CleanParams {
     #[arg(long = "source")]
     pub directory: Option<PathBuf>,
 
+    /// Removes only profiles expired before this date (YYYY-MM-DD) instead of now
+    #[arg(long = "before-date", value_parser = parse_date)]
+    pub before_date: Option<Date>,
+
+    /// Also removes profiles that will expire in days
+    #[arg(
+        short = 'd',
+        long = "expire-in-days",
+        value_parser = parse_days,
+        conflicts_with = "before_date"
+    )]
+    pub expire_in_days: Option<u64>,
+
+    /// Number of provisioning profiles to read at the same time, 0 means all cores
+    #[arg(short = 'j', long = "jobs", default_value_t = 0)]
+    pub jobs: usize,
+
     /// Whether to remove provisioning profiles permanently
     #[arg(long = "permanently")]
     pub permanently: bool,
@@ -99,11 +380,107 @@ pub struct ExtractParams {
     pub source: PathBuf,
     /// Directory where to place extracted provisioning profiles
     pub destination: PathBuf,
+
+    /// Whether to fail if the archive doesn't contain provisioning profiles
+    #[arg(long = "require-profiles")]
+    pub require_profiles: bool,
+
+    /// Whether to fail on corrupt provisioning profiles instead of skipping them
+    #[arg(long = "strict")]
+    pub strict: bool,
+
+    /// Password of an encrypted archive
+    #[arg(long = "password", short = 'p')]
+    pub password: Option<String>,
+
+    /// Whether to extract macOS provisioning profiles (`*.provisionprofile`) as well
+    #[arg(long = "mac")]
+    pub mac: bool,
+
+    /// A name of extracted provisioning profiles without the extension, `{uuid}`
+    /// and `{index}` are replaced with the uuid and the zero-padded index of a
+    /// profile [default: {uuid}]
+    #[arg(long = "output-name-format", value_parser = clap::builder::NonEmptyStringValueParser::new())]
+    pub output_name_format: Option<String>,
+
+    /// Extracts only provisioning profiles of this type: development, adhoc,
+    /// appstore or enterprise
+    #[arg(long = "profile-type", value_parser = parse_profile_type)]
+    pub profile_type: Option<ProfileType>,
+}
+
+#[derive(Debug, Default, PartialEq, Parser)]
+pub struct ValidateParams {
+    /// File paths of provisioning profiles
+    #[arg(num_args(1..), required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Debug, Default, PartialEq, Parser)]
+pub struct BackupParams {
+    /// A file path of the archive to create
+    pub archive: PathBuf,
+
+    /// A directory where to search provisioning profiles
+    #[arg(long = "source")]
+    pub directory: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, PartialEq, Parser)]
+pub struct RestoreParams {
+    /// A file path of the archive
+    pub archive: PathBuf,
+
+    /// A directory where to restore provisioning profiles
+    #[arg(long = "destination")]
+    pub destination: Option<PathBuf>,
+
+    /// Whether to abort if a hash of any provisioning profile doesn't match the manifest
+    #[arg(long = "verify")]
+    pub verify: bool,
+}
+
+#[derive(Debug, PartialEq, Parser)]
+pub struct ChangesParams {
+    /// A date (YYYY-MM-DD) since which profiles are considered new
+    #[arg(value_parser = parse_date)]
+    pub since: Date,
+
+    /// A directory where to search provisioning profiles
+    #[arg(long = "source")]
+    pub directory: Option<PathBuf>,
+
+    /// Number of provisioning profiles to read at the same time, 0 means all cores
+    #[arg(short = 'j', long = "jobs", default_value_t = 0)]
+    pub jobs: usize,
 }
 
-/// Runs the cli and returns the `Command`.
-pub fn run() -> Command {
-    Command::parse()
+/// Runs the cli and returns the `Cli`.
+///
+/// The `command` of the `Cli` is `None` only if the version is requested.
+pub fn run() -> Cli {
+    try_parse_from(std::env::args_os()).unwrap_or_else(|err| err.exit())
+}
+
+/// Parses the `Cli` from `args` and ensures that a subcommand is present unless
+/// the version is requested.
+fn try_parse_from<I, T>(args: I) -> result::Result<Cli, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let cli = Cli::try_parse_from(args)?;
+    match (&cli.command, cli.version_json) {
+        (None, false) => Err(Cli::command().error(
+            clap::error::ErrorKind::MissingSubcommand,
+            "a subcommand is required",
+        )),
+        (Some(_), true) => Err(Cli::command().error(
+            clap::error::ErrorKind::ArgumentConflict,
+            "'--version-json' cannot be used with a subcommand",
+        )),
+        _ => Ok(cli),
+    }
 }
 
 /// Parses and validates days argument.
@@ -115,17 +492,108 @@ fn parse_days(s: &str) -> result::Result<u64, String> {
     Ok(days as u64)
 }
 
+/// Parses an output format argument.
+fn parse_format(s: &str) -> result::Result<OutputFormat, String> {
+    s.parse()
+}
+
+/// Parses a profile type argument.
+fn parse_profile_type(s: &str) -> result::Result<ProfileType, String> {
+    s.parse()
+}
+
+/// Parses and validates a date argument, the date should be in the past.
+fn parse_date(s: &str) -> result::Result<Date, String> {
+    let date = Date::parse(s, format_description!("[year]-[month]-[day]"))
+        .map_err(|err| err.to_string())?;
+    if OffsetDateTime::from(SystemTime::now()).date() < date {
+        return Err(format!("should be a past date, got {}", date));
+    }
+    Ok(date)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Parses arguments and returns a `Cli`.
+    fn parse_cli<'a, I>(args: I) -> result::Result<Cli, clap::Error>
+    where
+        I: IntoIterator<Item = &'a str>,
+        ::std::ffi::OsString: From<&'a str>,
+    {
+        try_parse_from(std::iter::once("mprovision").chain(args))
+    }
+
     /// Parses arguments and returns a `Command`.
     fn parse<'a, I>(args: I) -> result::Result<Command, clap::Error>
     where
         I: IntoIterator<Item = &'a str>,
         ::std::ffi::OsString: From<&'a str>,
     {
-        Command::try_parse_from(std::iter::once("mprovision").chain(args))
+        parse_cli(args).map(|cli| cli.command.unwrap())
+    }
+
+    #[test]
+    fn version_json() {
+        assert!(parse_cli(["--version-json"]).unwrap().version_json);
+        assert!(parse_cli(["--version-json", "list"]).is_err());
+        assert!(parse_cli(["--version-json", "--verbose"]).is_err());
+    }
+
+    #[test]
+    fn without_subcommand_should_err() {
+        assert!(parse_cli([]).is_err());
+        assert!(parse_cli(["--verbose"]).is_err());
+    }
+
+    #[test]
+    fn warn_days_default() {
+        assert_eq!(parse_cli(["list"]).unwrap().warn_days, 30);
+    }
+
+    #[test]
+    fn warn_days() {
+        assert_eq!(
+            parse_cli(["list", "--warn-days", "7"]).unwrap(),
+            Cli {
+                command: Some(Command::List(ListParams::default())),
+                version_json: false,
+                warn_days: 7,
+                verbose: false,
+                quiet: false,
+            }
+        );
+        assert!(parse_cli(["list", "--warn-days", "18446744073709551615"]).is_err());
+    }
+
+    #[test]
+    fn verbose() {
+        assert!(
+            parse_cli(["extract", "app.ipa", ".", "--verbose"])
+                .unwrap()
+                .verbose
+        );
+        assert!(parse_cli(["-v", "list"]).unwrap().verbose);
+    }
+
+    #[test]
+    fn quiet() {
+        assert!(parse_cli(["list", "--quiet"]).unwrap().quiet);
+        assert!(parse_cli(["-q", "list"]).unwrap().quiet);
+    }
+
+    #[test]
+    fn quiet_with_verbose_should_err() {
+        assert!(parse_cli(["list", "-q", "-v"]).is_err());
+    }
+
+    #[test]
+    fn warn_days_before_subcommand() {
+        assert_eq!(
+            parse_cli(["--warn-days", "7", "clean"]).unwrap().warn_days,
+            7
+        );
     }
 
     #[test]
@@ -141,14 +609,41 @@ mod tests {
         assert_eq!(
             parse(["list", "--source", "."]).unwrap(),
             Command::List(ListParams {
-                text: None,
+                text: Vec::new(),
                 expire_in_days: None,
-                directory: Some(".".into()),
+                directories: vec![".".into()],
+                jobs: 0,
+                format: OutputFormat::Multiline,
                 oneline: false,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
             })
         );
     }
 
+    #[test]
+    fn list_with_several_sources() {
+        let expected = Command::List(ListParams {
+            directories: vec!["a".into(), "b".into(), "c".into()],
+            ..ListParams::default()
+        });
+        assert_eq!(
+            parse(["list", "--source", "a:b", "--source", "c"]).unwrap(),
+            expected
+        );
+    }
+
     #[test]
     fn list_with_empty_source_should_err() {
         assert!(parse(["list", "--source", ""]).is_err());
@@ -159,10 +654,25 @@ mod tests {
         assert_eq!(
             parse(["list", "--text", "abc"]).unwrap(),
             Command::List(ListParams {
-                text: Some("abc".to_string()),
+                text: vec!["abc".to_string()],
                 expire_in_days: None,
-                directory: None,
+                directories: Vec::new(),
+                jobs: 0,
+                format: OutputFormat::Multiline,
                 oneline: false,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
             })
         );
     }
@@ -172,10 +682,36 @@ mod tests {
         assert_eq!(
             parse(["list", "-t", "abc"]).unwrap(),
             Command::List(ListParams {
-                text: Some("abc".to_string()),
+                text: vec!["abc".to_string()],
                 expire_in_days: None,
-                directory: None,
+                directories: Vec::new(),
+                jobs: 0,
+                format: OutputFormat::Multiline,
                 oneline: false,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
+            })
+        );
+    }
+
+    #[test]
+    fn list_with_multiple_texts() {
+        assert_eq!(
+            parse(["list", "--text", "abc", "-t", "def"]).unwrap(),
+            Command::List(ListParams {
+                text: vec!["abc".to_string(), "def".to_string()],
+                ..ListParams::default()
             })
         );
     }
@@ -190,10 +726,25 @@ mod tests {
         assert_eq!(
             parse(["list", "--expire-in-days", "3"]).unwrap(),
             Command::List(ListParams {
-                text: None,
+                text: Vec::new(),
                 expire_in_days: Some(3),
-                directory: None,
+                directories: Vec::new(),
+                jobs: 0,
+                format: OutputFormat::Multiline,
                 oneline: false,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
             })
         );
     }
@@ -203,10 +754,25 @@ mod tests {
         assert_eq!(
             parse(["list", "-d", "3"]).unwrap(),
             Command::List(ListParams {
-                text: None,
+                text: Vec::new(),
                 expire_in_days: Some(3),
-                directory: None,
+                directories: Vec::new(),
+                jobs: 0,
+                format: OutputFormat::Multiline,
                 oneline: false,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
             })
         );
     }
@@ -235,10 +801,25 @@ mod tests {
             ])
             .unwrap(),
             Command::List(ListParams {
-                text: Some("abc".to_string()),
+                text: vec!["abc".to_string()],
                 expire_in_days: Some(3),
-                directory: Some(".".into()),
+                directories: vec![".".into()],
+                jobs: 0,
+                format: OutputFormat::Multiline,
                 oneline: false,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
             })
         );
     }
@@ -248,159 +829,644 @@ mod tests {
         assert_eq!(
             parse(["list", "-t", "abc", "-d", "3", "--source", ".",]).unwrap(),
             Command::List(ListParams {
-                text: Some("abc".to_string()),
+                text: vec!["abc".to_string()],
                 expire_in_days: Some(3),
-                directory: Some(".".into()),
+                directories: vec![".".into()],
+                jobs: 0,
+                format: OutputFormat::Multiline,
                 oneline: false,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
             })
         );
     }
 
     #[test]
-    fn list_with_oneline() {
+    fn list_with_jobs() {
         assert_eq!(
-            parse(["list", "--oneline"]).unwrap(),
+            parse(["list", "--jobs", "4"]).unwrap(),
             Command::List(ListParams {
-                text: None,
-                expire_in_days: None,
-                directory: None,
-                oneline: true
+                jobs: 4,
+                ..ListParams::default()
             })
         );
     }
 
     #[test]
-    fn show_uuid() {
+    fn list_with_oneline() {
         assert_eq!(
-            parse(["show", "abcd"]).unwrap(),
-            Command::ShowUuid(ShowUuidParams {
-                uuid: "abcd".to_string(),
-                directory: None,
+            parse(["list", "--oneline"]).unwrap(),
+            Command::List(ListParams {
+                text: Vec::new(),
+                expire_in_days: None,
+                directories: Vec::new(),
+                jobs: 0,
+                format: OutputFormat::Multiline,
+                oneline: true,
+                json: false,
+                sort_by: None,
+                local_time: false,
+                relative_dates: false,
+                count: false,
+                after: None,
+                limit: None,
+                mac: false,
+                width: None,
+                progress: false,
+                no_expired: false,
+                group_by_date: false,
+                platform: None,
             })
         );
     }
 
     #[test]
-    fn show_uuid_without_args_should_err() {
-        assert!(parse(["show", ""]).is_err());
-    }
-
-    #[test]
-    fn show_uuid_with_source() {
+    fn list_with_json() {
         assert_eq!(
-            parse(["show", "abcd", "--source", "."]).unwrap(),
-            Command::ShowUuid(ShowUuidParams {
-                uuid: "abcd".to_string(),
-                directory: Some(".".into()),
+            parse(["list", "--json"]).unwrap(),
+            Command::List(ListParams {
+                json: true,
+                ..ListParams::default()
             })
         );
     }
 
     #[test]
-    fn show_uuid_with_empty_source_should_err() {
-        assert!(parse(["show", "abcd", "--source", ""]).is_err());
+    fn list_with_json_and_oneline_should_err() {
+        assert!(parse(["list", "--json", "--oneline"]).is_err());
     }
 
     #[test]
-    fn show_file() {
-        assert_eq!(
-            parse(["show-file", "file.mprovision"]).unwrap(),
-            Command::ShowFile(ShowFileParams {
-                file: "file.mprovision".into(),
-            })
-        );
+    fn list_with_format() {
+        for (format, expected) in [
+            ("oneline", OutputFormat::Oneline),
+            ("csv", OutputFormat::Csv),
+            ("table", OutputFormat::Table),
+        ] {
+            assert_eq!(
+                parse(["list", "--format", format]).unwrap(),
+                Command::List(ListParams {
+                    format: expected,
+                    ..ListParams::default()
+                })
+            );
+        }
     }
 
     #[test]
-    fn show_file_with_multiple_paths_should_err() {
-        assert!(parse(["show-file", "file.mprovision", "."]).is_err());
+    fn list_with_unknown_format_should_err() {
+        assert!(parse(["list", "--format", "xml"]).is_err());
     }
 
     #[test]
-    fn show_file_with_empty_path_should_err() {
-        assert!(parse(["show-file", ""]).is_err());
+    fn list_with_format_and_oneline_should_err() {
+        assert!(parse(["list", "--format", "csv", "--oneline"]).is_err());
     }
 
     #[test]
-    fn remove() {
+    fn list_output_format() {
+        let params = |args| match parse(args).unwrap() {
+            Command::List(params) => params,
+            _ => unreachable!(),
+        };
         assert_eq!(
-            parse(["remove", "abcd"]).unwrap(),
-            Command::Remove(RemoveParams {
-                ids: vec!["abcd".to_string()],
-                directory: None,
-                permanently: false,
+            params(vec!["list"]).output_format(),
+            OutputFormat::Multiline
+        );
+        assert_eq!(
+            params(vec!["list", "--oneline"]).output_format(),
+            OutputFormat::Oneline
+        );
+        assert_eq!(
+            params(vec!["list", "--json"]).output_format(),
+            OutputFormat::Json
+        );
+        assert_eq!(
+            params(vec!["list", "--format", "table"]).output_format(),
+            OutputFormat::Table
+        );
+    }
+
+    #[test]
+    fn list_with_sort_by() {
+        assert_eq!(
+            parse(["list", "--sort-by", "expiration-date"]).unwrap(),
+            Command::List(ListParams {
+                sort_by: Some(SortBy::ExpirationDate),
+                ..ListParams::default()
             })
         );
     }
 
     #[test]
-    fn remove_single_permanently() {
+    fn list_with_local_time() {
         assert_eq!(
-            parse(["remove", "abcd", "--permanently"]).unwrap(),
-            Command::Remove(RemoveParams {
-                ids: vec!["abcd".to_string()],
-                directory: None,
-                permanently: true,
+            parse(["list", "--local-time"]).unwrap(),
+            Command::List(ListParams {
+                local_time: true,
+                ..ListParams::default()
             })
         );
     }
 
     #[test]
-    fn remove_multiple() {
+    fn list_with_relative_dates() {
         assert_eq!(
-            parse(["remove", "abcd", "ef"]).unwrap(),
-            Command::Remove(RemoveParams {
-                ids: vec!["abcd".to_string(), "ef".to_string()],
-                directory: None,
-                permanently: false,
+            parse(["list", "--relative-dates"]).unwrap(),
+            Command::List(ListParams {
+                relative_dates: true,
+                ..ListParams::default()
             })
         );
     }
 
     #[test]
-    fn remove_with_empty_arg_should_err() {
-        assert!(parse(["remove", ""]).is_err());
+    fn list_with_relative_dates_and_local_time_should_err() {
+        assert!(parse(["list", "--relative-dates", "--local-time"]).is_err());
     }
 
     #[test]
-    fn remove_single_with_source() {
+    fn list_with_after_and_limit() {
         assert_eq!(
-            parse(["remove", "abcd", "--source", "."]).unwrap(),
-            Command::Remove(RemoveParams {
-                ids: vec!["abcd".to_string()],
-                directory: Some(".".into()),
-                permanently: false,
+            parse(["list", "--after", "abcd", "--limit", "10"]).unwrap(),
+            Command::List(ListParams {
+                after: Some("abcd".to_owned()),
+                limit: Some(10),
+                ..ListParams::default()
             })
         );
+        assert!(parse(["list", "--after", ""]).is_err());
     }
 
     #[test]
-    fn remove_multiple_with_source() {
+    fn list_with_mac() {
         assert_eq!(
-            parse(["remove", "abcd", "ef", "--source", ".",]).unwrap(),
-            Command::Remove(RemoveParams {
-                ids: vec!["abcd".to_string(), "ef".to_string()],
-                directory: Some(".".into()),
-                permanently: false,
+            parse(["list", "--mac"]).unwrap(),
+            Command::List(ListParams {
+                mac: true,
+                ..ListParams::default()
             })
         );
     }
 
     #[test]
-    fn remove_with_permanently_and_source() {
+    fn list_with_progress() {
         assert_eq!(
-            parse(["remove", "abcd", "ef", "--permanently", "--source", ".",]).unwrap(),
-            Command::Remove(RemoveParams {
-                ids: vec!["abcd".to_string(), "ef".to_string()],
-                directory: Some(".".into()),
-                permanently: true,
+            parse(["list", "--progress"]).unwrap(),
+            Command::List(ListParams {
+                progress: true,
+                ..ListParams::default()
             })
         );
     }
 
     #[test]
-    fn remove_with_empty_source_should_err() {
-        assert!(parse(["remove", "abcd", "--source", ""]).is_err());
+    fn list_with_platform() {
+        assert_eq!(
+            parse(["list", "--platform", "macos"]).unwrap(),
+            Command::List(ListParams {
+                platform: Some("macos".into()),
+                ..ListParams::default()
+            })
+        );
+        assert!(parse(["list", "--platform", "android"]).is_err());
+    }
+
+    #[test]
+    fn list_without_expired() {
+        assert_eq!(
+            parse(["list", "--no-expired", "--expire-in-days", "30"]).unwrap(),
+            Command::List(ListParams {
+                no_expired: true,
+                expire_in_days: Some(30),
+                ..ListParams::default()
+            })
+        );
+    }
+
+    #[test]
+    fn list_grouped_by_date() {
+        assert_eq!(
+            parse(["list", "--group-by-date"]).unwrap(),
+            Command::List(ListParams {
+                group_by_date: true,
+                ..ListParams::default()
+            })
+        );
+        assert!(parse(["list", "--group-by-date", "--count"]).is_err());
+    }
+
+    #[test]
+    fn list_with_width() {
+        assert_eq!(
+            parse(["list", "--width", "40"]).unwrap(),
+            Command::List(ListParams {
+                width: Some(40),
+                ..ListParams::default()
+            })
+        );
+        assert!(parse(["list", "--width", "0"]).is_err());
+    }
+
+    #[test]
+    fn list_with_count() {
+        let expected = Command::List(ListParams {
+            count: true,
+            ..ListParams::default()
+        });
+        assert_eq!(parse(["list", "--count"]).unwrap(), expected);
+        assert_eq!(parse(["list", "-c"]).unwrap(), expected);
+    }
+
+    #[test]
+    fn list_with_unknown_sort_by_should_err() {
+        assert!(parse(["list", "--sort-by", "date"]).is_err());
+    }
+
+    #[test]
+    fn sort_by_parse() {
+        assert_eq!(SortBy::parse("name"), Ok(SortBy::Name));
+        assert!(SortBy::parse("date").is_err());
+    }
+
+    #[test]
+    fn show_uuid() {
+        assert_eq!(
+            parse(["show", "abcd"]).unwrap(),
+            Command::ShowUuid(ShowUuidParams {
+                uuid: Some("abcd".to_string()),
+                directory: None,
+                jobs: 0,
+                url: None,
+            })
+        );
+    }
+
+    #[test]
+    fn show_uuid_with_jobs() {
+        assert_eq!(
+            parse(["show", "abcd", "-j", "2"]).unwrap(),
+            Command::ShowUuid(ShowUuidParams {
+                uuid: Some("abcd".to_string()),
+                directory: None,
+                jobs: 2,
+                url: None,
+            })
+        );
+    }
+
+    #[test]
+    fn show_uuid_without_args_should_err() {
+        assert!(parse(["show", ""]).is_err());
+        assert!(parse(["show"]).is_err());
+    }
+
+    #[test]
+    fn show_url() {
+        assert_eq!(
+            parse(["show", "--url", "https://example.com/app.mobileprovision"]).unwrap(),
+            Command::ShowUuid(ShowUuidParams {
+                uuid: None,
+                directory: None,
+                jobs: 0,
+                url: Some("https://example.com/app.mobileprovision".to_string()),
+            })
+        );
+        assert!(parse(["show", "abcd", "--url", "https://example.com"]).is_err());
+    }
+
+    #[test]
+    fn show_uuid_with_source() {
+        assert_eq!(
+            parse(["show", "abcd", "--source", "."]).unwrap(),
+            Command::ShowUuid(ShowUuidParams {
+                uuid: Some("abcd".to_string()),
+                directory: Some(".".into()),
+                jobs: 0,
+                url: None,
+            })
+        );
+    }
+
+    #[test]
+    fn detect_show_args() {
+        let uuid = "8b6a4a3e-5c1d-4f6e-9a2b-1c3d5e7f9a0b";
+        assert_eq!(detect_show_arg(uuid), ShowArg::Uuid(uuid.to_owned()));
+        assert_eq!(detect_show_arg("abcd"), ShowArg::Uuid("abcd".to_owned()));
+        assert_eq!(
+            detect_show_arg("./profiles/abcd"),
+            ShowArg::FilePath("./profiles/abcd".into())
+        );
+        assert_eq!(
+            detect_show_arg("abcd.mobileprovision"),
+            ShowArg::FilePath("abcd.mobileprovision".into())
+        );
+        assert_eq!(
+            detect_show_arg("abcd.provisionprofile"),
+            ShowArg::FilePath("abcd.provisionprofile".into())
+        );
+    }
+
+    #[test]
+    fn show_uuid_with_empty_source_should_err() {
+        assert!(parse(["show", "abcd", "--source", ""]).is_err());
+    }
+
+    #[test]
+    fn show_file() {
+        assert_eq!(
+            parse(["show-file", "file.mprovision"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "file.mprovision".into(),
+                cert_info: false,
+                decode_base64: false,
+                pretty: false,
+                highlight: None,
+                raw: false,
+                base64: false,
+            })
+        );
+    }
+
+    #[test]
+    fn show_file_from_stdin() {
+        assert_eq!(
+            parse(["show-file", "-"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "-".into(),
+                cert_info: false,
+                decode_base64: false,
+                pretty: false,
+                highlight: None,
+                raw: false,
+                base64: false,
+            })
+        );
+    }
+
+    #[test]
+    fn show_file_with_cert_info() {
+        assert_eq!(
+            parse(["show-file", "file.mprovision", "--cert-info"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "file.mprovision".into(),
+                cert_info: true,
+                decode_base64: false,
+                pretty: false,
+                highlight: None,
+                raw: false,
+                base64: false,
+            })
+        );
+    }
+
+    #[test]
+    fn show_file_with_decode_base64() {
+        assert_eq!(
+            parse(["show-file", "file.mprovision", "--decode-base64"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "file.mprovision".into(),
+                cert_info: false,
+                decode_base64: true,
+                pretty: false,
+                highlight: None,
+                raw: false,
+                base64: false,
+            })
+        );
+    }
+
+    #[test]
+    fn show_file_with_pretty() {
+        assert_eq!(
+            parse(["show-file", "file.mprovision", "--pretty"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "file.mprovision".into(),
+                cert_info: false,
+                decode_base64: false,
+                pretty: true,
+                highlight: None,
+                raw: false,
+                base64: false,
+            })
+        );
+    }
+
+    #[test]
+    fn show_file_with_highlight() {
+        assert_eq!(
+            parse(["show-file", "file.mprovision", "--highlight", "app"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "file.mprovision".into(),
+                highlight: Some("app".into()),
+                ..ShowFileParams::default()
+            })
+        );
+        assert!(parse(["show-file", "file.mprovision", "--highlight", ""]).is_err());
+    }
+
+    #[test]
+    fn show_file_raw() {
+        assert_eq!(
+            parse(["show-file", "file.mprovision", "--raw"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "file.mprovision".into(),
+                raw: true,
+                ..ShowFileParams::default()
+            })
+        );
+        assert!(parse(["show-file", "file.mprovision", "--raw", "--pretty"]).is_err());
+    }
+
+    #[test]
+    fn show_file_base64() {
+        assert_eq!(
+            parse(["show-file", "file.mprovision", "--base64"]).unwrap(),
+            Command::ShowFile(ShowFileParams {
+                file: "file.mprovision".into(),
+                base64: true,
+                ..ShowFileParams::default()
+            })
+        );
+        assert!(parse(["show-file", "file.mprovision", "--base64", "--raw"]).is_err());
+    }
+
+    #[test]
+    fn install() {
+        assert_eq!(
+            parse(["install", "file.mobileprovision"]).unwrap(),
+            Command::Install(InstallParams {
+                file: Some("file.mobileprovision".into()),
+                ..InstallParams::default()
+            })
+        );
+        assert_eq!(
+            parse(["install", "--base64", "--destination", "."]).unwrap(),
+            Command::Install(InstallParams {
+                file: None,
+                base64: true,
+                destination: Some(".".into()),
+            })
+        );
+        assert!(parse(["install"]).is_err());
+        assert!(parse(["install", "file.mobileprovision", "--base64"]).is_err());
+    }
+
+    #[test]
+    fn show_file_with_multiple_paths_should_err() {
+        assert!(parse(["show-file", "file.mprovision", "."]).is_err());
+    }
+
+    #[test]
+    fn show_file_with_empty_path_should_err() {
+        assert!(parse(["show-file", ""]).is_err());
+    }
+
+    #[test]
+    fn remove() {
+        assert_eq!(
+            parse(["remove", "abcd"]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["abcd".to_string()],
+                directory: None,
+                jobs: 0,
+                permanently: false,
+                glob: false,
+                fail_on_missing: false,
+            })
+        );
+    }
+
+    #[test]
+    fn remove_single_permanently() {
+        assert_eq!(
+            parse(["remove", "abcd", "--permanently"]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["abcd".to_string()],
+                directory: None,
+                jobs: 0,
+                permanently: true,
+                glob: false,
+                fail_on_missing: false,
+            })
+        );
+    }
+
+    #[test]
+    fn remove_multiple() {
+        assert_eq!(
+            parse(["remove", "abcd", "ef"]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["abcd".to_string(), "ef".to_string()],
+                directory: None,
+                jobs: 0,
+                permanently: false,
+                glob: false,
+                fail_on_missing: false,
+            })
+        );
+    }
+
+    #[test]
+    fn remove_with_glob() {
+        assert_eq!(
+            parse(["remove", "com.example.*", "--glob"]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["com.example.*".to_string()],
+                directory: None,
+                jobs: 0,
+                permanently: false,
+                glob: true,
+                fail_on_missing: false,
+            })
+        );
+    }
+
+    #[test]
+    fn remove_with_fail_on_missing() {
+        assert_eq!(
+            parse(["remove", "abcd", "--fail-on-missing"]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["abcd".to_string()],
+                fail_on_missing: true,
+                ..RemoveParams::default()
+            })
+        );
+        assert_eq!(
+            parse(["remove", "abcd", "--strict-ids"]).unwrap(),
+            parse(["remove", "abcd", "--fail-on-missing"]).unwrap()
+        );
+    }
+
+    #[test]
+    fn remove_with_fail_on_missing_and_glob_should_err() {
+        assert!(parse(["remove", "com.*", "--glob", "--fail-on-missing"]).is_err());
+    }
+
+    #[test]
+    fn remove_with_empty_arg_should_err() {
+        assert!(parse(["remove", ""]).is_err());
+    }
+
+    #[test]
+    fn remove_single_with_source() {
+        assert_eq!(
+            parse(["remove", "abcd", "--source", "."]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["abcd".to_string()],
+                directory: Some(".".into()),
+                jobs: 0,
+                permanently: false,
+                glob: false,
+                fail_on_missing: false,
+            })
+        );
+    }
+
+    #[test]
+    fn remove_multiple_with_source() {
+        assert_eq!(
+            parse(["remove", "abcd", "ef", "--source", ".",]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["abcd".to_string(), "ef".to_string()],
+                directory: Some(".".into()),
+                jobs: 0,
+                permanently: false,
+                glob: false,
+                fail_on_missing: false,
+            })
+        );
+    }
+
+    #[test]
+    fn remove_with_permanently_and_source() {
+        assert_eq!(
+            parse(["remove", "abcd", "ef", "--permanently", "--source", ".",]).unwrap(),
+            Command::Remove(RemoveParams {
+                ids: vec!["abcd".to_string(), "ef".to_string()],
+                directory: Some(".".into()),
+                jobs: 0,
+                permanently: true,
+                glob: false,
+                fail_on_missing: false,
+            })
+        );
+    }
+
+    #[test]
+    fn remove_with_empty_source_should_err() {
+        assert!(parse(["remove", "abcd", "--source", ""]).is_err());
     }
 
     #[test]
@@ -409,6 +1475,9 @@ mod tests {
             parse(["clean"]).unwrap(),
             Command::Clean(CleanParams {
                 directory: None,
+                before_date: None,
+                expire_in_days: None,
+                jobs: 0,
                 permanently: false,
             })
         );
@@ -420,6 +1489,9 @@ mod tests {
             parse(["clean", "--permanently"]).unwrap(),
             Command::Clean(CleanParams {
                 directory: None,
+                before_date: None,
+                expire_in_days: None,
+                jobs: 0,
                 permanently: true,
             })
         );
@@ -431,6 +1503,9 @@ mod tests {
             parse(["clean", "--source", "."]).unwrap(),
             Command::Clean(CleanParams {
                 directory: Some(".".into()),
+                before_date: None,
+                expire_in_days: None,
+                jobs: 0,
                 permanently: false,
             })
         );
@@ -442,11 +1517,50 @@ mod tests {
             parse(["clean", "--permanently", "--source", "."]).unwrap(),
             Command::Clean(CleanParams {
                 directory: Some(".".into()),
+                before_date: None,
+                expire_in_days: None,
+                jobs: 0,
                 permanently: true,
             })
         );
     }
 
+    #[test]
+    fn clean_with_before_date() {
+        assert_eq!(
+            parse(["clean", "--before-date", "2020-01-31"]).unwrap(),
+            Command::Clean(CleanParams {
+                directory: None,
+                before_date: Some(time::macros::date!(2020 - 01 - 31)),
+                expire_in_days: None,
+                jobs: 0,
+                permanently: false,
+            })
+        );
+    }
+
+    #[test]
+    fn clean_with_invalid_before_date_should_err() {
+        assert!(parse(["clean", "--before-date", "2020-13-01"]).is_err());
+        assert!(parse(["clean", "--before-date", "31.01.2020"]).is_err());
+    }
+
+    #[test]
+    fn clean_with_future_before_date_should_err() {
+        assert!(parse(["clean", "--before-date", "9999-01-01"]).is_err());
+    }
+
+    #[test]
+    fn clean_with_expire_in_days() {
+        let expected = Command::Clean(CleanParams {
+            expire_in_days: Some(7),
+            ..CleanParams::default()
+        });
+        assert_eq!(parse(["clean", "--expire-in-days", "7"]).unwrap(), expected);
+        assert_eq!(parse(["clean", "-d", "7"]).unwrap(), expected);
+        assert!(parse(["clean", "-d", "7", "--before-date", "2020-01-01"]).is_err());
+    }
+
     #[test]
     fn clean_with_empty_source_should_err() {
         assert!(parse(["clean", "--source", ""]).is_err());
@@ -459,8 +1573,105 @@ mod tests {
             Command::Extract(ExtractParams {
                 source: "app.ipa".into(),
                 destination: ".".into(),
+                require_profiles: false,
+                strict: false,
+                password: None,
+                mac: false,
+                output_name_format: None,
+                profile_type: None,
+            })
+        );
+    }
+
+    #[test]
+    fn extract_with_require_profiles() {
+        assert_eq!(
+            parse(["extract", "app.ipa", ".", "--require-profiles"]).unwrap(),
+            Command::Extract(ExtractParams {
+                source: "app.ipa".into(),
+                destination: ".".into(),
+                require_profiles: true,
+                strict: false,
+                password: None,
+                mac: false,
+                output_name_format: None,
+                profile_type: None,
+            })
+        );
+    }
+
+    #[test]
+    fn extract_with_strict() {
+        assert_eq!(
+            parse(["extract", "app.ipa", ".", "--strict"]).unwrap(),
+            Command::Extract(ExtractParams {
+                source: "app.ipa".into(),
+                destination: ".".into(),
+                require_profiles: false,
+                strict: true,
+                password: None,
+                mac: false,
+                output_name_format: None,
+                profile_type: None,
+            })
+        );
+    }
+
+    #[test]
+    fn extract_with_output_name_format() {
+        assert_eq!(
+            parse([
+                "extract",
+                "app.ipa",
+                ".",
+                "--output-name-format",
+                "{index}_{uuid}"
+            ])
+            .unwrap(),
+            Command::Extract(ExtractParams {
+                source: "app.ipa".into(),
+                destination: ".".into(),
+                output_name_format: Some("{index}_{uuid}".into()),
+                ..ExtractParams::default()
             })
         );
+        assert!(parse(["extract", "app.ipa", ".", "--output-name-format", ""]).is_err());
+    }
+
+    #[test]
+    fn extract_with_profile_type() {
+        assert_eq!(
+            parse(["extract", "app.ipa", ".", "--profile-type", "adhoc"]).unwrap(),
+            Command::Extract(ExtractParams {
+                source: "app.ipa".into(),
+                destination: ".".into(),
+                profile_type: Some(ProfileType::AdHoc),
+                ..ExtractParams::default()
+            })
+        );
+        assert!(parse(["extract", "app.ipa", ".", "--profile-type", "distribution"]).is_err());
+    }
+
+    #[test]
+    fn extract_with_password() {
+        let expected = Command::Extract(ExtractParams {
+            source: "app.ipa".into(),
+            destination: ".".into(),
+            require_profiles: false,
+            strict: false,
+            password: Some("secret".into()),
+            mac: false,
+            output_name_format: None,
+            profile_type: None,
+        });
+        assert_eq!(
+            parse(["extract", "app.ipa", ".", "--password", "secret"]).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse(["extract", "app.ipa", ".", "-p", "secret"]).unwrap(),
+            expected
+        );
     }
 
     #[test]
@@ -472,4 +1683,101 @@ mod tests {
     fn extract_without_args_should_err() {
         assert!(parse(["extract"]).is_err());
     }
+
+    #[test]
+    fn changes() {
+        assert_eq!(
+            parse(["changes", "2024-01-15", "--source", "."]).unwrap(),
+            Command::Changes(ChangesParams {
+                since: Date::from_calendar_date(2024, time::Month::January, 15).unwrap(),
+                directory: Some(".".into()),
+                jobs: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn changes_without_date_should_err() {
+        assert!(parse(["changes"]).is_err());
+        assert!(parse(["changes", "v1.0.0"]).is_err());
+    }
+
+    #[test]
+    fn show_diff() {
+        assert_eq!(
+            parse(["show-diff", "1.mobileprovision", "2.mobileprovision"]).unwrap(),
+            Command::ShowDiff(ShowDiffParams {
+                old_file: "1.mobileprovision".into(),
+                new_file: "2.mobileprovision".into(),
+                context: false,
+            })
+        );
+        assert_eq!(
+            parse([
+                "show-diff",
+                "1.mobileprovision",
+                "2.mobileprovision",
+                "--context"
+            ])
+            .unwrap(),
+            Command::ShowDiff(ShowDiffParams {
+                old_file: "1.mobileprovision".into(),
+                new_file: "2.mobileprovision".into(),
+                context: true,
+            })
+        );
+    }
+
+    #[test]
+    fn backup() {
+        assert_eq!(
+            parse(["backup", "profiles.tar.gz", "--source", "."]).unwrap(),
+            Command::Backup(BackupParams {
+                archive: "profiles.tar.gz".into(),
+                directory: Some(".".into()),
+            })
+        );
+        assert!(parse(["backup"]).is_err());
+    }
+
+    #[test]
+    fn restore() {
+        assert_eq!(
+            parse(["restore", "profiles.tar.gz"]).unwrap(),
+            Command::Restore(RestoreParams {
+                archive: "profiles.tar.gz".into(),
+                ..RestoreParams::default()
+            })
+        );
+        assert_eq!(
+            parse([
+                "restore",
+                "profiles.tar.gz",
+                "--destination",
+                ".",
+                "--verify"
+            ])
+            .unwrap(),
+            Command::Restore(RestoreParams {
+                archive: "profiles.tar.gz".into(),
+                destination: Some(".".into()),
+                verify: true,
+            })
+        );
+    }
+
+    #[test]
+    fn validate() {
+        assert_eq!(
+            parse(["validate", "1.mobileprovision", "2.mobileprovision"]).unwrap(),
+            Command::Validate(ValidateParams {
+                files: vec!["1.mobileprovision".into(), "2.mobileprovision".into()],
+            })
+        );
+    }
+
+    #[test]
+    fn validate_without_args_should_err() {
+        assert!(parse(["validate"]).is_err());
+    }
 }
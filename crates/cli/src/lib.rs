@@ -0,0 +1,4 @@
+//! Formatters of the **mprovision** command line tool. They are exposed as a
+//! library for benchmarks, it isn't meant to be used by other crates.
+
+pub mod profile_formatters;
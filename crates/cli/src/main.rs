@@ -1,150 +1,836 @@
-use cli::Command;
+use changes::Changes;
+use cli::{Command, SortBy};
+use colored::Colorize;
+use mp::format::OutputFormat;
 use mprovision as mp;
-use profile_formatters::{format_multiline, format_oneline};
+use mprovision_cli::profile_formatters::{
+    self, decode_base64_data, format_certificate_expiration, format_csv, format_json,
+    format_json_count, format_multiline, format_oneline, format_table, highlight,
+};
+use std::borrow::Cow;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 use std::result;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, SystemTime};
 use std::{
     fs,
-    io::{self, Read, Write},
+    io::{self, IsTerminal, Read, Write},
 };
-use zip::ZipArchive;
+use zip::{result::ZipError, ZipArchive};
 
+mod changes;
 mod cli;
-mod profile_formatters;
 
-type Result = result::Result<(), main_error::MainError>;
+type Result = result::Result<(), Error>;
 
-fn main() -> Result {
-    match cli::run() {
-        Command::List(cli::ListParams {
-            text,
-            expire_in_days,
+/// An exit code when no provisioning profiles are found.
+const EXIT_NOT_FOUND: u8 = 2;
+
+/// Represents an error of a command.
+enum Error {
+    /// Denotes that no provisioning profiles are found, `main` exits with
+    /// [`EXIT_NOT_FOUND`] code printing the error if any.
+    NotFound(Option<mp::error::Error>),
+    /// Denotes any other error, `main` exits with `1` code.
+    Other(main_error::MainError),
+}
+
+impl<E: Into<Box<dyn std::error::Error>>> From<E> for Error {
+    fn from(e: E) -> Self {
+        Self::Other(main_error::MainError::from(e))
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(Error::NotFound(err)) => {
+            if let Some(err) = err {
+                eprintln!("Error: {}", err);
+            }
+            ExitCode::from(EXIT_NOT_FOUND)
+        }
+        Err(Error::Other(err)) => {
+            eprintln!("Error: {:?}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run() -> Result {
+    let cli = cli::run();
+    let warn_days = cli.warn_days;
+    let verbose = cli.verbose;
+    let quiet = cli.quiet;
+    let Some(command) = cli.command else {
+        return print_version_json();
+    };
+    match command {
+        Command::List(params) => list(params, warn_days, verbose, quiet),
+        Command::ShowUuid(cli::ShowUuidParams {
+            uuid,
             directory,
-            oneline,
-        }) => list(
-            &text,
-            expire_in_days,
-            mp::dir_or_default(directory)?,
-            oneline,
-        ),
-        Command::ShowUuid(cli::ShowUuidParams { uuid, directory }) => {
-            let dir = mp::dir_or_default(directory)?;
-            let profile = mp::filter_dir(&dir, |profile| profile.info.uuid == uuid)?
-                .into_iter()
-                .next()
-                .ok_or_else(|| format!("Failed to find provisioning profile for '{}'", uuid))?;
-            show_file(&profile.path)
-        }
-        Command::ShowFile(cli::ShowFileParams { file }) => show_file(&file),
+            jobs,
+            url,
+        }) => {
+            if let Some(url) = url {
+                return show_url(&url);
+            }
+            // clap requires the uuid if there is no url.
+            let file = match cli::detect_show_arg(&uuid.unwrap_or_default()) {
+                cli::ShowArg::Uuid(uuid) => {
+                    let dir = dir_or_default(directory)?;
+                    mp::find_by_uuid(&dir, &uuid, jobs)?.path
+                }
+                cli::ShowArg::FilePath(path) => path,
+            };
+            show_file(cli::ShowFileParams {
+                file,
+                ..cli::ShowFileParams::default()
+            })
+        }
+        Command::ShowFile(params) => show_file(params),
+        Command::ShowDiff(params) => show_diff(params),
         Command::Remove(cli::RemoveParams {
             ids,
             directory,
+            jobs,
             permanently,
+            glob,
+            fail_on_missing,
         }) => {
-            let dir = mp::dir_or_default(directory)?;
-            let profiles = mp::filter_dir(&dir, |profile| profile.info.has_ids(&ids))?;
-            remove_profiles(&profiles, permanently)
+            let dir = dir_or_default(directory)?;
+            let profiles = if glob {
+                find_by_ids_or_patterns(&dir, &ids, jobs)?
+            } else {
+                find_by_ids(&dir, &ids, jobs, fail_on_missing, quiet)?
+            };
+            remove_profiles(&profiles, permanently, warn_days)
         }
         Command::Clean(cli::CleanParams {
             directory,
+            before_date,
+            expire_in_days,
+            jobs,
             permanently,
         }) => {
-            let dir = mp::dir_or_default(directory)?;
-            let date = SystemTime::now();
-            let profiles = mp::filter_dir(&dir, |profile| profile.info.expiration_date <= date)?;
-            remove_profiles(&profiles, permanently)
+            let dir = dir_or_default(directory)?;
+            let date = match (before_date, expire_in_days) {
+                (Some(date), _) => SystemTime::from(date.midnight().assume_utc()),
+                (None, Some(days)) => SystemTime::now() + Duration::from_secs(days * 24 * 60 * 60),
+                (None, None) => SystemTime::now(),
+            };
+            let profiles = mp::filter_dir_with_concurrency(&dir, jobs, |profile| {
+                profile.info.expiration_date <= date
+            })?;
+            remove_profiles(&profiles, permanently, warn_days)
         }
-        Command::Extract(cli::ExtractParams {
-            source,
-            destination,
-        }) => extract(source, destination),
+        Command::Extract(params) => extract(params, verbose, quiet),
+        Command::Install(params) => install(params),
+        Command::Validate(cli::ValidateParams { files }) => validate(&files),
+        Command::Changes(params) => changes(params),
+        Command::Backup(params) => backup(params),
+        Command::Restore(params) => restore(params),
     }
 }
 
-fn list(
-    text: &Option<String>,
-    expires_in_days: Option<u64>,
-    dir: PathBuf,
-    oneline: bool,
-) -> Result {
-    let date =
-        expires_in_days.map(|days| SystemTime::now() + Duration::from_secs(days * 24 * 60 * 60));
-    let filter_string = text.as_ref();
-    let mut profiles = mp::filter_dir(&dir, |profile| match (date, filter_string) {
-        (Some(date), Some(string)) => {
-            profile.info.expiration_date <= date && profile.info.contains(string)
-        }
-        (Some(date), _) => profile.info.expiration_date <= date,
-        (_, Some(string)) => profile.info.contains(string),
-        (_, _) => true,
+/// Returns `dir` or the default directory warning if the latter doesn't exist.
+fn dir_or_default(dir: Option<PathBuf>) -> result::Result<PathBuf, mp::error::Error> {
+    let is_default = dir.is_none();
+    let dir = mp::dir_or_default(dir)?;
+    if is_default && !dir.exists() {
+        eprintln!(
+            "Default directory does not exist: {}. Use --source to specify a custom path.",
+            dir.display()
+        );
+    }
+    Ok(dir)
+}
+
+/// Returns profiles that have any of `ids` warning about ids without profiles.
+///
+/// Fails with [`Error::NotFound`] if there are no profiles at all and, when
+/// `fail_on_missing` is `true`, if any of `ids` has no profile.
+fn find_by_ids(
+    dir: &Path,
+    ids: &[String],
+    jobs: usize,
+    fail_on_missing: bool,
+    quiet: bool,
+) -> result::Result<Vec<mp::profile::Profile>, Error> {
+    let mut profiles: Vec<mp::profile::Profile> = Vec::new();
+    let mut missing = false;
+    for (id, profile) in mp::find_by_ids(dir, ids, jobs)? {
+        match profile {
+            Some(profile) => {
+                if !profiles.iter().any(|p| p.path == profile.path) {
+                    profiles.push(profile);
+                }
+            }
+            None => {
+                missing = true;
+                if !quiet {
+                    writeln!(io::stderr(), "Warning: no profile found for id '{}'", id)?;
+                }
+            }
+        }
+    }
+    if profiles.is_empty() {
+        return Err(not_found(ids));
+    }
+    if missing && fail_on_missing {
+        // Don't need to show anything – all missing ids are already printed.
+        return Err(String::new().into());
+    }
+    Ok(profiles)
+}
+
+/// Returns an error that no profiles are found for `ids`.
+fn not_found(ids: &[String]) -> Error {
+    Error::NotFound(Some(mp::error::Error::NotFound(ids.join(", "))))
+}
+
+/// Returns profiles that have any of `ids` or bundle ids matching glob patterns
+/// among `ids`.
+fn find_by_ids_or_patterns(
+    dir: &Path,
+    ids: &[String],
+    jobs: usize,
+) -> result::Result<Vec<mp::profile::Profile>, Error> {
+    let (patterns, exact_ids): (Vec<&String>, Vec<&String>) =
+        ids.iter().partition(|id| id.contains(['*', '?']));
+    let patterns = patterns
+        .into_iter()
+        .map(|pattern| glob::Pattern::new(pattern))
+        .collect::<result::Result<Vec<_>, _>>()?;
+    let profiles = mp::filter_dir_with_concurrency(dir, jobs, |profile| {
+        profile.info.has_ids(&exact_ids) || profile.info.matches_bundle_id_patterns(&patterns)
     })?;
-    profiles.sort_by(|a, b| a.info.creation_date.cmp(&b.info.creation_date));
-    let stdout = io::stdout();
-    let mut stdout = stdout.lock();
-    let format = if oneline {
-        format_oneline
+    if profiles.is_empty() {
+        return Err(not_found(ids));
+    }
+    Ok(profiles)
+}
+
+/// Represents the json output of the version.
+#[derive(Debug, serde::Serialize)]
+struct VersionJson {
+    version: &'static str,
+    build_date: &'static str,
+}
+
+fn print_version_json() -> Result {
+    let version = VersionJson {
+        version: env!("CARGO_PKG_VERSION"),
+        build_date: env!("VERGEN_BUILD_DATE"),
+    };
+    println!("{}", serde_json::to_string_pretty(&version)?);
+    Ok(())
+}
+
+fn list(params: cli::ListParams, warn_days: u64, verbose: bool, quiet: bool) -> Result {
+    let format = params.output_format();
+    let cli::ListParams {
+        text,
+        expire_in_days,
+        directories,
+        jobs,
+        sort_by,
+        local_time,
+        relative_dates,
+        count,
+        after,
+        limit,
+        mac,
+        width,
+        progress,
+        platform,
+        no_expired,
+        group_by_date,
+        ..
+    } = params;
+    if group_by_date && matches!(format, OutputFormat::Json | OutputFormat::Csv) {
+        return Err(format!("--group-by-date can't be used with the {} format", format).into());
+    }
+    if local_time {
+        // Determines the offset before reading profiles in multiple threads.
+        profile_formatters::local_offset();
+    }
+    let dirs = if directories.is_empty() {
+        vec![dir_or_default(None)?]
     } else {
-        format_multiline
+        directories
+    };
+    let dirs = dirs.iter().map(PathBuf::as_path).collect::<Vec<_>>();
+    let date =
+        expire_in_days.map(|days| SystemTime::now() + Duration::from_secs(days * 24 * 60 * 60));
+    let token = cancel_on_interrupt();
+    let filter = mp::profile::FilterOptions {
+        texts: text,
+        expires_before: date,
+        expires_after: no_expired.then(SystemTime::now),
+        platform,
+        ..Default::default()
+    };
+    let predicate = |profile: &mp::profile::Profile| profile.info.matches_filter(&filter);
+    let report_progress = |processed: usize, total: usize| {
+        if progress {
+            // Errors of writing to stderr don't affect the listing.
+            let _ = write!(io::stderr(), "\r[{}/{}]", processed, total);
+        }
     };
+    let options = mp::ScanOptions {
+        concurrency: jobs,
+        token: Some(token.clone()),
+        progress: Some(&report_progress),
+        include_mac: mac,
+        ..Default::default()
+    };
+    let mut profiles = mp::filter_dirs_with_options(&dirs, predicate, &options)?;
+    if progress {
+        writeln!(io::stderr())?;
+    }
+    let sort_by = match std::env::var(SortBy::ENV) {
+        Ok(value) => SortBy::parse(&value).map_err(|err| format!("{}: {}", SortBy::ENV, err))?,
+        Err(_) => sort_by.unwrap_or_default(),
+    };
+    sort_profiles(&mut profiles, sort_by);
+    if profiles.is_empty() && filter.texts.is_empty() && !quiet && no_file_paths(&dirs, mac)? {
+        let dirs = dirs
+            .iter()
+            .map(|dir| dir.display().to_string())
+            .collect::<Vec<_>>();
+        writeln!(
+            io::stderr(),
+            "Warning: No provisioning profiles found in {}. Have you installed any?",
+            dirs.join(", ")
+        )?;
+    }
+    let profiles = paginate(profiles, after.as_deref(), limit)?;
+    let found = !profiles.is_empty();
+    if count {
+        print_count(profiles.len(), format)?;
+    } else {
+        let width = match width {
+            Some(width) => Some(usize::from(width)),
+            None if std::env::var_os("COLUMNS").is_some() || io::stdout().is_terminal() => {
+                Some(profile_formatters::terminal_width())
+            }
+            None => None,
+        };
+        if group_by_date {
+            // Groups by the same dates as displayed.
+            let offset = profile_formatters::offset(local_time);
+            let groups = mp::group_by_creation_date(profiles, offset);
+            for (i, (date, profiles)) in groups.iter().enumerate() {
+                if i > 0 {
+                    println!();
+                }
+                println!("{}", date.to_string().bold());
+                print_profiles(
+                    profiles,
+                    format,
+                    warn_days,
+                    local_time,
+                    relative_dates,
+                    verbose,
+                    width,
+                )?;
+            }
+        } else {
+            print_profiles(
+                &profiles,
+                format,
+                warn_days,
+                local_time,
+                relative_dates,
+                verbose,
+                width,
+            )?;
+        }
+    }
+    if token.load(Ordering::Relaxed) {
+        return Err("Interrupted, only profiles read so far are listed".into());
+    }
+    if !found {
+        return Err(Error::NotFound(None));
+    }
+    Ok(())
+}
+
+/// Returns a token that is set on the first Ctrl-C, the next one terminates
+/// the process as usual.
+#[cfg(unix)]
+fn cancel_on_interrupt() -> Arc<AtomicBool> {
+    static TOKEN: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    extern "C" fn handle(_: libc::c_int) {
+        if let Some(token) = TOKEN.get() {
+            token.store(true, Ordering::Relaxed);
+        }
+    }
+    let token = TOKEN.get_or_init(Arc::default).clone();
+    // SAFETY: the handler only stores to an atomic that is initialized above.
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handle as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESETHAND;
+        libc::sigaction(libc::SIGINT, &action, std::ptr::null_mut());
+    }
+    token
+}
+
+/// Returns a token that is never set, Ctrl-C terminates the process as usual.
+#[cfg(not(unix))]
+fn cancel_on_interrupt() -> Arc<AtomicBool> {
+    Arc::default()
+}
+
+/// Prints a `count` of profiles as json or plain text depending on a `format`.
+fn print_count(count: usize, format: OutputFormat) -> Result {
+    if format == OutputFormat::Json {
+        println!("{}", format_json_count(count)?);
+    } else {
+        println!("{}", count);
+    }
+    Ok(())
+}
+
+/// Prints `profiles` in a `format` truncating names to fit into `width` columns
+/// if it's given.
+fn print_profiles(
+    profiles: &[mp::profile::Profile],
+    format: OutputFormat,
+    warn_days: u64,
+    local_time: bool,
+    relative_dates: bool,
+    verbose: bool,
+    width: Option<usize>,
+) -> Result {
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    match format {
+        OutputFormat::Json => {
+            writeln!(&mut stdout, "{}", format_json(profiles)?)?;
+            return Ok(());
+        }
+        OutputFormat::Csv => {
+            write!(&mut stdout, "{}", format_csv(profiles)?)?;
+            return Ok(());
+        }
+        _ => (),
+    }
+    if verbose {
+        writeln!(&mut stdout, "Found {} profiles:", profiles.len())?;
+    }
+    if format == OutputFormat::Table {
+        write!(
+            &mut stdout,
+            "{}",
+            format_table(profiles, local_time, width)?
+        )?;
+        return Ok(());
+    }
     for (i, profile) in profiles.iter().enumerate() {
-        let separator = if oneline || i + 1 == profiles.len() {
-            ""
+        if format == OutputFormat::Oneline {
+            writeln!(
+                &mut stdout,
+                "{}",
+                format_oneline(profile, local_time, relative_dates, width)?
+            )?;
+        } else {
+            let separator = if i + 1 == profiles.len() { "" } else { "\n" };
+            format_multiline(
+                profile,
+                warn_days,
+                local_time,
+                relative_dates,
+                verbose,
+                width,
+                &mut stdout,
+            )?;
+            writeln!(&mut stdout, "{}", separator)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `profiles` after the one with `after` uuid, at most `limit` of them.
+fn paginate(
+    mut profiles: Vec<mp::profile::Profile>,
+    after: Option<&str>,
+    limit: Option<usize>,
+) -> result::Result<Vec<mp::profile::Profile>, mp::error::Error> {
+    if let Some(after) = after {
+        let index = profiles
+            .iter()
+            .position(|profile| profile.info.uuid == after)
+            .ok_or_else(|| mp::error::Error::NotFound(after.to_owned()))?;
+        profiles.drain(..=index);
+    }
+    if let Some(limit) = limit {
+        profiles.truncate(limit);
+    }
+    Ok(profiles)
+}
+
+fn no_file_paths(dirs: &[&Path], mac: bool) -> result::Result<bool, mp::error::Error> {
+    for dir in dirs {
+        let found = if mac {
+            mp::file_paths_all(dir)?.next().is_some()
         } else {
-            "\n"
+            mp::file_paths(dir)?.next().is_some()
         };
-        writeln!(&mut stdout, "{}{}", format(profile)?, separator)?;
+        if found {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+fn sort_profiles(profiles: &mut [mp::profile::Profile], sort_by: SortBy) {
+    match sort_by {
+        SortBy::CreationDate => profiles.sort_by_key(|profile| profile.info.creation_date),
+        SortBy::ExpirationDate => profiles.sort(),
+        SortBy::Name => profiles.sort_by(|a, b| a.info.name.cmp(&b.info.name)),
+        SortBy::Uuid => profiles.sort_by(|a, b| a.info.uuid.cmp(&b.info.uuid)),
+    }
+}
+
+fn show_diff(params: cli::ShowDiffParams) -> Result {
+    let cli::ShowDiffParams {
+        old_file,
+        new_file,
+        context,
+    } = params;
+    let old = mp::profile::Profile::from_file(&old_file)?;
+    let new = mp::profile::Profile::from_file(&new_file)?;
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    writeln!(&mut stdout, "--- {}", old_file.display())?;
+    writeln!(&mut stdout, "+++ {}", new_file.display())?;
+    for diff in old.info.diff(&new.info) {
+        if diff.is_changed() {
+            let removed = format!("-{}: {}", diff.field, diff.old);
+            let added = format!("+{}: {}", diff.field, diff.new);
+            writeln!(&mut stdout, "{}", removed.red())?;
+            writeln!(&mut stdout, "{}", added.green())?;
+        } else if context {
+            writeln!(&mut stdout, " {}: {}", diff.field, diff.old)?;
+        }
     }
     Ok(())
 }
 
-fn show_file(path: &Path) -> Result {
-    let xml = mp::show(path)?;
-    writeln!(io::stdout(), "{}", xml)?;
+/// Downloads a provisioning profile from an `url` and prints its xml like `show` does.
+#[cfg(feature = "http")]
+fn show_url(url: &str) -> Result {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+    let xml = runtime.block_on(mp::show_url(url))?;
+    println!("{}", xml);
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+fn show_url(_url: &str) -> Result {
+    Err("Downloading profiles requires mprovision to be built with the 'http' feature".into())
+}
+
+fn show_file(params: cli::ShowFileParams) -> Result {
+    let cli::ShowFileParams {
+        file: path,
+        cert_info,
+        decode_base64,
+        pretty,
+        highlight: term,
+        raw,
+        base64,
+    } = params;
+    if raw {
+        mp::show_raw(&path, &mut io::stdout().lock())?;
+        return Ok(());
+    }
+    if base64 {
+        let mut data = Vec::new();
+        mp::show_raw(&path, &mut data)?;
+        println!("{}", mp::encode_base64(&data));
+        return Ok(());
+    }
+    let xml = if path == Path::new("-") {
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        let xml = mp::show_data(&buf)?;
+        if pretty {
+            mp::pretty_print(&xml)?
+        } else {
+            xml
+        }
+    } else if pretty {
+        mp::show_pretty(&path)?
+    } else {
+        mp::show(&path)?
+    };
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    let output = if decode_base64 {
+        Cow::Owned(decode_base64_data(&xml))
+    } else {
+        Cow::Borrowed(xml.as_str())
+    };
+    match term {
+        Some(term) => writeln!(&mut stdout, "{}", highlight(&output, &term))?,
+        None => writeln!(&mut stdout, "{}", output)?,
+    }
+    if cert_info {
+        let info = mp::profile::Info::from_clean_xml(xml.as_bytes())?;
+        for certificate in &info.developer_certificates {
+            writeln!(
+                &mut stdout,
+                "{}",
+                format_certificate_expiration(certificate)?
+            )?;
+        }
+    }
     Ok(())
 }
 
-fn extract(source: PathBuf, destination: PathBuf) -> Result {
+fn extract(params: cli::ExtractParams, verbose: bool, quiet: bool) -> Result {
+    let cli::ExtractParams {
+        source,
+        destination,
+        require_profiles,
+        strict,
+        password,
+        mac,
+        output_name_format,
+        profile_type,
+    } = params;
+    let is_profile = if mac {
+        mp::is_any_provision_profile
+    } else {
+        mp::is_mobileprovision
+    };
+    let output_name_format = output_name_format.as_deref().unwrap_or("{uuid}");
     if !destination.exists() {
-        fs::create_dir_all(&destination)?;
+        fs::create_dir_all(&destination).map_err(mp::error::Error::with_path(&destination))?;
     }
     if !destination.is_dir() {
         return Err(format!("Destination '{}' is not a directory", destination.display()).into());
     }
-    let mut archive = ZipArchive::new(fs::File::open(source)?)?;
+    let archive = fs::File::open(&source).map_err(mp::error::Error::with_path(&source))?;
+    let mut archive = ZipArchive::new(archive).map_err(mp::error::Error::from)?;
+    let total = archive
+        .file_names()
+        .filter(|name| is_profile(Path::new(name)))
+        .count();
+    let mut extracted = 0;
+    let mut non_profiles = 0;
+    let mut corrupt = 0;
+    let mut filtered = 0;
     for i in 0..archive.len() {
-        let mut file = archive.by_index(i)?;
-        let Some(path) = file.enclosed_name().map(|name| name.to_path_buf()) else { continue };
-        if mp::is_mobileprovision(&path) {
-            continue;
-        }
+        let file = match &password {
+            Some(password) => archive.by_index_decrypt(i, password.as_bytes()),
+            None => archive.by_index(i),
+        };
+        let mut file = file.map_err(|err| match err {
+            ZipError::InvalidPassword => mp::error::Error::Own(format!(
+                "Invalid password for archive '{}'",
+                source.display()
+            )),
+            ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) => {
+                mp::error::Error::Own(format!(
+                    "Archive '{}' is encrypted, use --password to provide a password",
+                    source.display()
+                ))
+            }
+            err => err.into(),
+        })?;
+        let path = match file.enclosed_name() {
+            Some(path) if is_profile(&path) => path,
+            _ => {
+                non_profiles += 1;
+                continue;
+            }
+        };
         let mut buf: Vec<u8> = Vec::with_capacity(file.size() as usize);
         file.read_to_end(&mut buf)?;
-        let info = mp::profile::Info::from_xml_data(&buf)
-            .ok_or_else(|| format!("Failed to decode {}", path.display()))?;
-        let file_name = format!("{}.mobileprovision", info.uuid);
+        let info = match mp::profile::Info::from_xml_data_partial(&buf) {
+            Ok(partial) => {
+                if let Some(warning) = partial.warnings.first().filter(|_| strict) {
+                    return Err(format!("Failed to decode {}: {}", path.display(), warning).into());
+                }
+                if !quiet {
+                    for warning in &partial.warnings {
+                        writeln!(io::stderr(), "Warning: {}: {}", path.display(), warning)?;
+                    }
+                }
+                partial.info
+            }
+            Err(_) if strict => return Err(format!("Failed to decode {}", path.display()).into()),
+            Err(_) => {
+                if !quiet {
+                    writeln!(
+                        io::stderr(),
+                        "Warning: Skipped {}, failed to decode",
+                        path.display()
+                    )?;
+                }
+                corrupt += 1;
+                continue;
+            }
+        };
+        if profile_type.is_some_and(|profile_type| info.profile_type() != profile_type) {
+            filtered += 1;
+            continue;
+        }
+        // The uuid comes from an untrusted archive and becomes a file name.
+        if !info.has_valid_uuid() {
+            return Err(
+                format!("Invalid profile uuid '{}' in {}", info.uuid, path.display()).into(),
+            );
+        }
+        let extension = if mp::is_provisionprofile(&path) {
+            mp::EXT_PROVISIONPROFILE
+        } else {
+            mp::EXT_MOBILEPROVISION
+        };
+        let file_name = format!(
+            "{}.{}",
+            expand_filename_template(output_name_format, &info.uuid, extracted, total),
+            extension
+        );
         let mut buf_cursor = io::Cursor::new(buf);
         let outpath = destination.join(file_name);
-        let mut outfile = fs::File::create(outpath)?;
+        let mut outfile =
+            fs::File::create(&outpath).map_err(mp::error::Error::with_path(&outpath))?;
         io::copy(&mut buf_cursor, &mut outfile)?;
+        extracted += 1;
+    }
+    if verbose {
+        let mut stderr = io::stderr();
+        writeln!(&mut stderr, "Skipped {} non-profile entries", non_profiles)?;
+        writeln!(
+            &mut stderr,
+            "Skipped {} profile(s) that failed to decode",
+            corrupt
+        )?;
+        if let Some(profile_type) = profile_type {
+            writeln!(
+                &mut stderr,
+                "Skipped {} profile(s) that aren't {}",
+                filtered, profile_type
+            )?;
+        }
+    }
+    if require_profiles && extracted == 0 {
+        return Err(match profile_type.filter(|_| filtered > 0) {
+            Some(profile_type) => format!(
+                "No {} provisioning profiles found in '{}'",
+                profile_type,
+                source.display()
+            ),
+            None => format!("No provisioning profiles found in '{}'", source.display()),
+        }
+        .into());
     }
     Ok(())
 }
 
-fn remove_profiles(profiles: &[mp::profile::Profile], permanently: bool) -> Result {
+fn install(params: cli::InstallParams) -> Result {
+    let cli::InstallParams {
+        file, destination, ..
+    } = params;
+    let data = match &file {
+        Some(path) => fs::read(path).map_err(mp::error::Error::with_path(path))?,
+        None => {
+            let mut base64 = String::new();
+            io::stdin().read_to_string(&mut base64)?;
+            mp::decode_base64(&base64)?
+        }
+    };
+    let info = mp::info_from_xml_data(&data)
+        .map_err(|err| format!("Couldn't parse provisioning profile: {}", err))?;
+    // The uuid becomes a file name, so it can't be allowed to point outside
+    // of the destination.
+    if !info.has_valid_uuid() {
+        return Err(format!("Invalid profile uuid '{}'", info.uuid).into());
+    }
+    let extension = if info.is_for_platform("macos") {
+        mp::EXT_PROVISIONPROFILE
+    } else {
+        mp::EXT_MOBILEPROVISION
+    };
+    let dir = destination.map_or_else(mp::directory, Ok)?;
+    fs::create_dir_all(&dir).map_err(mp::error::Error::with_path(&dir))?;
+    let path = dir.join(format!("{}.{}", info.uuid, extension));
+    fs::write(&path, &data).map_err(mp::error::Error::with_path(&path))?;
+    println!("Installed {}", path.display());
+    Ok(())
+}
+
+/// Replaces `{uuid}` and `{index}` tokens of a `template` with the `uuid` and
+/// the `index` zero-padded to the number of digits of the `total`.
+fn expand_filename_template(template: &str, uuid: &str, index: usize, total: usize) -> String {
+    let width = total.to_string().len();
+    template
+        .replace("{uuid}", uuid)
+        .replace("{index}", &format!("{:0width$}", index, width = width))
+}
+
+fn changes(params: cli::ChangesParams) -> Result {
+    let cli::ChangesParams {
+        since,
+        directory,
+        jobs,
+    } = params;
+    let dir = dir_or_default(directory)?;
+    let profiles = mp::filter_dir_with_concurrency(&dir, jobs, |_| true)?
+        .into_iter()
+        .map(|profile| {
+            let modified = fs::metadata(&profile.path)
+                .and_then(|metadata| metadata.modified())
+                .map_err(mp::error::Error::with_path(&profile.path))?;
+            Ok((profile, modified))
+        })
+        .collect::<result::Result<Vec<_>, mp::error::Error>>()?;
+    let changes = Changes::new(&profiles, SystemTime::from(since.midnight().assume_utc()));
+    print!("{}", changes.to_markdown(since));
+    Ok(())
+}
+
+fn validate(files: &[PathBuf]) -> Result {
     let mut errors_exist = false;
     let stdout = io::stdout();
     let mut stdout = stdout.lock();
-    for (i, profile) in profiles.iter().enumerate() {
-        match remove(&profile.path, permanently) {
-            Ok(()) => {
-                let separator = if i + 1 == profiles.len() { "" } else { "\n" };
-                writeln!(&mut stdout, "{}{}", format_multiline(profile)?, separator)?
-            }
+    for file in files {
+        let profile = match mp::profile::ProfileRef::from_file(file) {
+            Ok(profile) => profile,
             Err(err) => {
                 errors_exist = true;
-                writeln!(io::stderr(), "{}", err)?
+                writeln!(
+                    io::stderr(),
+                    "{}: {}",
+                    file.display(),
+                    err.to_string().red()
+                )?;
+                continue;
             }
+        };
+        let errors = mp::validation::validate_profile_ref(&profile);
+        if errors.is_empty() {
+            writeln!(&mut stdout, "{}: {}", file.display(), "valid".green())?;
+        }
+        for error in errors {
+            errors_exist = true;
+            writeln!(
+                io::stderr(),
+                "{}: {}",
+                file.display(),
+                error.to_string().red()
+            )?;
         }
     }
     if errors_exist {
@@ -155,11 +841,74 @@ fn remove_profiles(profiles: &[mp::profile::Profile], permanently: bool) -> Resu
     }
 }
 
-fn remove(file_path: &Path, permanently: bool) -> result::Result<(), Box<dyn std::error::Error>> {
-    if permanently {
-        std::fs::remove_file(file_path)?;
+fn backup(params: cli::BackupParams) -> Result {
+    let cli::BackupParams { archive, directory } = params;
+    let dir = dir_or_default(directory)?;
+    let manifest = mp::backup::backup(&dir, &archive)?;
+    println!(
+        "Backed up {} profile(s) to {}.",
+        manifest.len(),
+        archive.display()
+    );
+    Ok(())
+}
+
+fn restore(params: cli::RestoreParams) -> Result {
+    let cli::RestoreParams {
+        archive,
+        destination,
+        verify,
+    } = params;
+    let dir = destination.map_or_else(mp::directory, Ok)?;
+    let manifest = mp::backup::restore(&archive, &dir, verify)?;
+    println!(
+        "Restored {} profile(s) to {}.",
+        manifest.len(),
+        dir.display()
+    );
+    Ok(())
+}
+
+/// Represents results of removing provisioning profiles.
+#[derive(Debug, Default)]
+struct RemoveResult<'a> {
+    removed: Vec<&'a mp::profile::Profile>,
+    failed: Vec<&'a mp::profile::Profile>,
+}
+
+/// Removes `profiles` printing each of them before it's removed, so there is a
+/// record of what was removed even if the process is killed midway.
+fn remove_profiles(profiles: &[mp::profile::Profile], permanently: bool, warn_days: u64) -> Result {
+    let mut result = RemoveResult::default();
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+    for (i, profile) in profiles.iter().enumerate() {
+        let separator = if i + 1 == profiles.len() { "" } else { "\n" };
+        format_multiline(profile, warn_days, false, false, false, None, &mut stdout)?;
+        writeln!(&mut stdout, "{}", separator)?;
+        stdout.flush()?;
+        match mp::remove(&profile.path, permanently) {
+            Ok(()) => result.removed.push(profile),
+            Err(err) => {
+                result.failed.push(profile);
+                writeln!(io::stderr(), "{}", err)?
+            }
+        }
+    }
+    writeln!(
+        &mut stdout,
+        "\nRemoved {} profile(s).",
+        result.removed.len()
+    )?;
+    if !result.failed.is_empty() {
+        writeln!(
+            &mut stdout,
+            "Failed to remove {} profile(s).",
+            result.failed.len()
+        )?;
+        // Don't need to show anything – all errors are already printed.
+        Err(String::new().into())
     } else {
-        trash::delete(file_path)?;
+        Ok(())
     }
-    Ok(())
 }
@@ -1,39 +1,632 @@
+use base64::prelude::{Engine, BASE64_STANDARD};
 use colored::Colorize;
-use mprovision::profile::Profile;
+use mprovision::profile::{ExpiryStatus, Profile};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::error::Error;
+use std::io::Write;
+use std::sync::OnceLock;
+use std::time::SystemTime;
 use time::error::Format;
+use time::format_description::well_known::Rfc3339;
 use time::format_description::FormatItem;
 use time::macros::format_description;
-use time::OffsetDateTime;
+use time::{OffsetDateTime, UtcOffset};
+use x509_parser::parse_x509_certificate;
+
+/// Returns an offset of the local timezone or `None` if it can't be determined.
+///
+/// The offset is determined once, so this function should be called before
+/// spawning threads since on some platforms it's unsound to get it in
+/// multithreaded programs.
+pub fn local_offset() -> Option<UtcOffset> {
+    static LOCAL_OFFSET: OnceLock<Option<UtcOffset>> = OnceLock::new();
+    *LOCAL_OFFSET.get_or_init(|| OffsetDateTime::now_local().ok().map(|now| now.offset()))
+}
+
+/// Returns the offset of the local timezone if `local_time` is `true` and UTC
+/// otherwise.
+///
+/// Falls back to UTC if the local timezone can't be determined.
+pub fn offset(local_time: bool) -> UtcOffset {
+    local_time
+        .then(local_offset)
+        .flatten()
+        .unwrap_or(UtcOffset::UTC)
+}
+
+/// Converts a `date` to the local timezone if `local_time` is `true`, see
+/// [`offset`].
+fn date_time(date: SystemTime, local_time: bool) -> OffsetDateTime {
+    OffsetDateTime::from(date).to_offset(offset(local_time))
+}
+
+/// Returns a human-readable time relative to now, e.g. "expires in 3 days".
+fn relative_time(t: SystemTime) -> String {
+    relative_time_since(t, SystemTime::now())
+}
+
+/// Returns a human-readable time of `t` relative to `now`.
+fn relative_time_since(t: SystemTime, now: SystemTime) -> String {
+    const DAY: u64 = 24 * 60 * 60;
+    fn amount(days: u64) -> String {
+        let (count, unit) = match days {
+            0..=13 => (days, "day"),
+            14..=59 => (days / 7, "week"),
+            _ => (days / 30, "month"),
+        };
+        if count == 1 {
+            format!("{} {}", count, unit)
+        } else {
+            format!("{} {}s", count, unit)
+        }
+    }
+    match t.duration_since(now) {
+        Ok(duration) if duration.as_secs() < DAY => "expires today".to_owned(),
+        Ok(duration) => format!("expires in {}", amount(duration.as_secs() / DAY)),
+        Err(err) if err.duration().as_secs() < DAY => "expired today".to_owned(),
+        Err(err) => format!("expired {} ago", amount(err.duration().as_secs() / DAY)),
+    }
+}
+
+/// Returns a width of the terminal in columns.
+///
+/// The width is taken from the `COLUMNS` environment variable, then from the
+/// terminal attached to stdout, and defaults to 80.
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+        .or_else(terminal_size)
+        .unwrap_or(80)
+}
+
+/// Returns a number of columns of the terminal attached to stdout.
+#[cfg(unix)]
+fn terminal_size() -> Option<usize> {
+    // SAFETY: `winsize` is a plain struct that `ioctl` fills on success.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+    (result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+/// Returns a number of columns of the terminal attached to stdout.
+#[cfg(not(unix))]
+fn terminal_size() -> Option<usize> {
+    None
+}
+
+/// Truncates a `text` to `width` characters replacing the end with `...`.
+fn truncate(text: &str, width: usize) -> Cow<'_, str> {
+    if text.chars().count() <= width {
+        return Cow::Borrowed(text);
+    }
+    let mut truncated = text
+        .chars()
+        .take(width.saturating_sub(3))
+        .collect::<String>();
+    truncated.push_str(&"..."[..width.min(3)]);
+    Cow::Owned(truncated)
+}
 
 /// Formats a profile in one line.
-pub fn format_oneline(profile: &Profile) -> Result<String, Format> {
+///
+/// The expiration date is in the local timezone if `local_time` is `true` or
+/// relative to now if `relative_dates` is `true`. The name is truncated so the
+/// line fits into `width` columns if it's given.
+pub fn format_oneline(
+    profile: &Profile,
+    local_time: bool,
+    relative_dates: bool,
+    width: Option<usize>,
+) -> Result<String, Format> {
     const FMT: &[FormatItem] = format_description!("[year]-[month]-[day]");
+    let expiration_date = if relative_dates {
+        relative_time(profile.info.expiration_date)
+    } else {
+        date_time(profile.info.expiration_date, local_time).format(FMT)?
+    };
+    let name = match width {
+        Some(width) => {
+            let used = profile.info.uuid.chars().count()
+                + expiration_date.chars().count()
+                + profile.info.app_identifier.as_str().chars().count()
+                + 3;
+            truncate(&profile.info.name, width.saturating_sub(used))
+        }
+        None => Cow::Borrowed(profile.info.name.as_str()),
+    };
     Ok(format!(
         "{} {} {} {}",
         profile.info.uuid.yellow(),
-        OffsetDateTime::from(profile.info.expiration_date)
-            .format(FMT)?
-            .blue(),
-        profile.info.app_identifier.green(),
-        profile.info.name
+        expiration_date.blue(),
+        profile.info.app_identifier.as_str().green(),
+        name
     ))
 }
 
-/// Formats a profile multilined.
-pub fn format_multiline(profile: &Profile) -> Result<String, Format> {
-    const FMT: &[FormatItem] =
+/// Writes a profile multilined into a `writer`.
+///
+/// Dates are highlighted depending on whether the profile expires within `warn_days`
+/// and are in the local timezone if `local_time` is `true`. If `relative_dates` is
+/// `true` only the expiration date relative to now is shown. If `verbose` is `true`
+/// the total validity span of the profile is shown as well. The name is truncated
+/// to `width` columns if it's given.
+pub fn format_multiline<W: Write>(
+    profile: &Profile,
+    warn_days: u64,
+    local_time: bool,
+    relative_dates: bool,
+    verbose: bool,
+    width: Option<usize>,
+    writer: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    const UTC_FMT: &[FormatItem] =
         format_description!("[year]-[month]-[day] [hour]:[minute]:[second] UTC");
-    let dates = format!(
-        "{} - {}",
-        OffsetDateTime::from(profile.info.creation_date).format(FMT)?,
-        OffsetDateTime::from(profile.info.expiration_date).format(FMT)?,
-    )
-    .blue();
-    Ok(format!(
-        "{}\n{}\n{}\n{}",
+    const OFFSET_FMT: &[FormatItem] = format_description!(
+        "[year]-[month]-[day] [hour]:[minute]:[second] [offset_hour sign:mandatory]:[offset_minute]"
+    );
+    let format_date = |date| {
+        let date_time = date_time(date, local_time);
+        if date_time.offset().is_utc() {
+            date_time.format(UTC_FMT)
+        } else {
+            date_time.format(OFFSET_FMT)
+        }
+    };
+    write!(
+        writer,
+        "{}\n{}\n{}",
         profile.info.uuid.yellow(),
-        profile.info.app_identifier.green(),
-        profile.info.name,
-        dates
+        profile.info.app_identifier.as_str().green(),
+        width.map_or(Cow::Borrowed(profile.info.name.as_str()), |width| {
+            truncate(&profile.info.name, width)
+        })
+    )?;
+    if !profile.info.app_id_name.is_empty() {
+        write!(writer, "\nApp ID: {}", profile.info.app_id_name)?;
+    }
+    match profile.info.provisioned_device_count() {
+        Some(1) => write!(writer, "\n1 device")?,
+        Some(count) => write!(writer, "\n{} devices", count)?,
+        None => write!(writer, "\nAll devices")?,
+    }
+    let dates = if relative_dates {
+        relative_time(profile.info.expiration_date)
+    } else {
+        format!(
+            "{} - {}",
+            format_date(profile.info.creation_date)?,
+            format_date(profile.info.expiration_date)?,
+        )
+    };
+    let dates = match profile.info.expiry_status(warn_days) {
+        ExpiryStatus::Expired => dates.red(),
+        ExpiryStatus::ExpiringSoon => dates.yellow(),
+        ExpiryStatus::Valid => dates.blue(),
+    };
+    write!(writer, "\n{}", dates)?;
+    // Verbose output falls back to the duration for profiles without `TimeToLive`.
+    let days = match profile.info.time_to_live_days() {
+        0 if verbose => profile.info.duration_days(),
+        days => u64::from(days),
+    };
+    match days {
+        0 => (),
+        1 => write!(writer, "\nValid for: 1 day total")?,
+        days => write!(writer, "\nValid for: {} days total", days)?,
+    }
+    Ok(())
+}
+
+/// Formats a profile multilined.
+///
+/// The same as [`format_multiline`] but returns a `String`.
+pub fn format_multiline_string(
+    profile: &Profile,
+    warn_days: u64,
+    local_time: bool,
+    relative_dates: bool,
+    verbose: bool,
+) -> Result<String, Box<dyn Error>> {
+    let mut buf = Vec::new();
+    format_multiline(
+        profile,
+        warn_days,
+        local_time,
+        relative_dates,
+        verbose,
+        None,
+        &mut buf,
+    )?;
+    Ok(String::from_utf8(buf)?)
+}
+
+/// Formats profiles as comma separated values with a header.
+///
+/// Dates are in RFC3339 format, fields that contain commas, quotes or line
+/// breaks are quoted.
+pub fn format_csv(profiles: &[Profile]) -> Result<String, Format> {
+    fn escape(field: &str) -> String {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_owned()
+        }
+    }
+    let mut csv = "uuid,name,app_identifier,creation_date,expiration_date\n".to_owned();
+    for profile in profiles {
+        let fields = [
+            escape(&profile.info.uuid),
+            escape(&profile.info.name),
+            escape(profile.info.app_identifier.as_str()),
+            OffsetDateTime::from(profile.info.creation_date).format(&Rfc3339)?,
+            OffsetDateTime::from(profile.info.expiration_date).format(&Rfc3339)?,
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    Ok(csv)
+}
+
+/// Formats profiles as a table with aligned columns and a header followed by a separator.
+///
+/// Expiration dates are in the local timezone if `local_time` is `true`. Names
+/// are truncated so rows fit into `width` columns if it's given.
+pub fn format_table(
+    profiles: &[Profile],
+    local_time: bool,
+    width: Option<usize>,
+) -> Result<String, Format> {
+    const FMT: &[FormatItem] = format_description!("[year]-[month]-[day]");
+    let mut rows = vec![[
+        "UUID".to_owned(),
+        "EXPIRES".to_owned(),
+        "APP IDENTIFIER".to_owned(),
+        "NAME".to_owned(),
+    ]];
+    for profile in profiles {
+        rows.push([
+            profile.info.uuid.clone(),
+            date_time(profile.info.expiration_date, local_time).format(FMT)?,
+            profile.info.app_identifier.to_string(),
+            profile.info.name.clone(),
+        ]);
+    }
+    let column_widths = |rows: &[[String; 4]]| {
+        let mut widths = [0; 4];
+        for row in rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        widths
+    };
+    if let Some(width) = width {
+        let used = column_widths(&rows)[..3].iter().sum::<usize>() + 3 * 2;
+        for row in &mut rows {
+            row[3] = truncate(&row[3], width.saturating_sub(used)).into_owned();
+        }
+    }
+    let widths = column_widths(&rows);
+    // The separator follows the header even if there are no profiles.
+    rows.insert(1, widths.map(|width| "-".repeat(width)));
+    let mut table = String::new();
+    for row in &rows {
+        let line = row
+            .iter()
+            .zip(widths)
+            .map(|(cell, width)| format!("{:width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    Ok(table)
+}
+
+/// Formats an expiration date of a DER encoded certificate.
+pub fn format_certificate_expiration(der: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    const FMT: &[FormatItem] = format_description!("[year]-[month]-[day]");
+    let (_, certificate) = parse_x509_certificate(der)
+        .map_err(|err| format!("Failed to parse developer certificate: {}", err))?;
+    let expiration_date = certificate.validity().not_after.to_datetime();
+    Ok(format!(
+        "[Certificate expires: {}]",
+        expiration_date.format(FMT)?
     ))
 }
+
+/// Highlights all occurrences of a `term` in a `text`.
+///
+/// ASCII letters are matched ignoring case, the matched text keeps its case.
+pub fn highlight(text: &str, term: &str) -> String {
+    let find = |text: &str| {
+        text.char_indices().map(|(i, _)| i).find(|&i| {
+            text.as_bytes()[i..]
+                .get(..term.len())
+                .is_some_and(|bytes| bytes.eq_ignore_ascii_case(term.as_bytes()))
+        })
+    };
+    if term.is_empty() {
+        return text.to_owned();
+    }
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(i) = find(rest) {
+        let end = i + term.len();
+        result.push_str(&rest[..i]);
+        result.push_str(&rest[i..end].yellow().to_string());
+        rest = &rest[end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Replaces base64 encoded values of `<data>` elements in a plist `xml` with
+/// their decoded UTF-8 text or hex dump if the text is not printable.
+///
+/// Values that can't be decoded are left as is.
+pub fn decode_base64_data(xml: &str) -> String {
+    const START: &str = "<data>";
+    const END: &str = "</data>";
+    let mut result = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(start_i) = rest.find(START).map(|i| i + START.len()) {
+        let Some(end_i) = rest[start_i..].find(END).map(|i| start_i + i) else {
+            break;
+        };
+        result.push_str(&rest[..start_i]);
+        let encoded = rest[start_i..end_i]
+            .chars()
+            .filter(|c| !c.is_ascii_whitespace())
+            .collect::<String>();
+        match BASE64_STANDARD.decode(encoded) {
+            Ok(data) => result.push_str(&decoded_text(data)),
+            Err(_) => result.push_str(&rest[start_i..end_i]),
+        }
+        rest = &rest[end_i..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Returns XML escaped UTF-8 text of `data` if it's printable or a hex dump otherwise.
+fn decoded_text(data: Vec<u8>) -> String {
+    match String::from_utf8(data) {
+        Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => text
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;"),
+        Ok(text) => hex(text.as_bytes()),
+        Err(err) => hex(err.as_bytes()),
+    }
+}
+
+fn hex(data: &[u8]) -> String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// A version of the json output, should be incremented on breaking changes.
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+/// Represents the json output of a list of profiles.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonOutput {
+    pub schema_version: u32,
+    pub count: usize,
+    pub profiles: Vec<ProfileJson>,
+}
+
+/// Represents a profile in the json output.
+///
+/// It's separate from `Profile` to keep the output stable when internal types change.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileJson {
+    pub path: String,
+    pub uuid: String,
+    pub name: String,
+    pub app_identifier: String,
+    pub creation_date: String,
+    pub expiration_date: String,
+}
+
+impl ProfileJson {
+    fn new(profile: &Profile) -> Result<Self, Format> {
+        Ok(Self {
+            path: profile.path.display().to_string(),
+            uuid: profile.info.uuid.clone(),
+            name: profile.info.name.clone(),
+            app_identifier: profile.info.app_identifier.to_string(),
+            creation_date: OffsetDateTime::from(profile.info.creation_date).format(&Rfc3339)?,
+            expiration_date: OffsetDateTime::from(profile.info.expiration_date).format(&Rfc3339)?,
+        })
+    }
+}
+
+/// Formats profiles as a json document.
+pub fn format_json(profiles: &[Profile]) -> Result<String, Box<dyn std::error::Error>> {
+    let output = JsonOutput {
+        schema_version: JSON_SCHEMA_VERSION,
+        count: profiles.len(),
+        profiles: profiles
+            .iter()
+            .map(ProfileJson::new)
+            .collect::<Result<_, _>>()?,
+    };
+    Ok(serde_json::to_string_pretty(&output)?)
+}
+
+/// Represents the json output of a number of profiles.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct JsonCountOutput {
+    pub count: usize,
+}
+
+/// Formats a number of profiles as a json document.
+pub fn format_json_count(count: usize) -> Result<String, serde_json::Error> {
+    serde_json::to_string_pretty(&JsonCountOutput { count })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mprovision::profile::Info;
+    use std::time::{Duration, SystemTime};
+
+    fn profile() -> Profile {
+        Profile {
+            path: "/profiles/1.mobileprovision".into(),
+            info: Info {
+                uuid: "123".into(),
+                name: "name".into(),
+                app_identifier: "TEAM.com.example.app".into(),
+                expiration_date: SystemTime::UNIX_EPOCH + Duration::from_secs(24 * 60 * 60),
+                ..Info::empty()
+            },
+        }
+    }
+
+    #[test]
+    fn multiline_in_utc() {
+        let multiline = format_multiline_string(&profile(), 30, false, false, false).unwrap();
+        assert!(multiline.contains("1970-01-01 00:00:00 UTC - 1970-01-02 00:00:00 UTC"));
+    }
+
+    #[test]
+    fn highlight_ignoring_case() {
+        assert_eq!(
+            highlight("<string>TestApp</string> testapp", "testapp"),
+            format!(
+                "<string>{}</string> {}",
+                "TestApp".yellow(),
+                "testapp".yellow()
+            )
+        );
+        assert_eq!(highlight("Größe", "öß"), format!("Gr{}e", "öß".yellow()));
+        assert_eq!(highlight("abc", "d"), "abc");
+        assert_eq!(highlight("abc", ""), "abc");
+    }
+
+    #[test]
+    fn decode_base64_data_as_text_or_hex() {
+        let xml = "<key>a</key>\n<data>\n\taGVsbG8g\n\tPHdvcmxkPg==\n</data>\n\
+                   <key>b</key><data>AAH/</data><key>c</key><data>?</data>";
+        assert_eq!(
+            decode_base64_data(xml),
+            "<key>a</key>\n<data>hello &lt;world&gt;</data>\n\
+             <key>b</key><data>0001ff</data><key>c</key><data>?</data>"
+        );
+    }
+
+    #[test]
+    fn multiline_with_time_to_live() {
+        let mut profile = profile();
+        assert!(!format_multiline_string(&profile, 30, false, false, false)
+            .unwrap()
+            .contains("Valid for"));
+        profile.info.time_to_live = 365;
+        assert!(format_multiline_string(&profile, 30, false, false, false)
+            .unwrap()
+            .ends_with("\nValid for: 365 days total"));
+    }
+
+    #[test]
+    fn multiline_verbose_with_duration() {
+        assert!(
+            !format_multiline_string(&profile(), 30, false, false, false)
+                .unwrap()
+                .contains("Valid for")
+        );
+        assert!(format_multiline_string(&profile(), 30, false, false, true)
+            .unwrap()
+            .ends_with("\nValid for: 1 day total"));
+    }
+
+    #[test]
+    fn relative_times() {
+        const DAY: u64 = 24 * 60 * 60;
+        let now = SystemTime::UNIX_EPOCH + Duration::from_secs(1000 * DAY);
+        let cases = [
+            (now + Duration::from_secs(60), "expires today"),
+            (now + Duration::from_secs(DAY), "expires in 1 day"),
+            (now + Duration::from_secs(3 * DAY), "expires in 3 days"),
+            (now + Duration::from_secs(15 * DAY), "expires in 2 weeks"),
+            (now + Duration::from_secs(125 * DAY), "expires in 4 months"),
+            (now - Duration::from_secs(60), "expired today"),
+            (now - Duration::from_secs(5 * DAY), "expired 5 days ago"),
+            (now - Duration::from_secs(30 * DAY), "expired 4 weeks ago"),
+        ];
+        for (t, expected) in cases {
+            assert_eq!(relative_time_since(t, now), expected);
+        }
+    }
+
+    #[test]
+    fn csv() {
+        let mut profile = profile();
+        profile.info.name = "name, \"quoted\"".into();
+        assert_eq!(
+            format_csv(&[profile]).unwrap(),
+            "uuid,name,app_identifier,creation_date,expiration_date\n\
+             123,\"name, \"\"quoted\"\"\",TEAM.com.example.app,\
+             1970-01-01T00:00:00Z,1970-01-02T00:00:00Z\n"
+        );
+    }
+
+    #[test]
+    fn table() {
+        assert_eq!(
+            format_table(&[profile()], false, None).unwrap(),
+            "UUID  EXPIRES     APP IDENTIFIER        NAME\n\
+             ----  ----------  --------------------  ----\n\
+             123   1970-01-02  TEAM.com.example.app  name\n"
+        );
+        assert_eq!(
+            format_table(&[], false, None).unwrap(),
+            "UUID  EXPIRES  APP IDENTIFIER  NAME\n\
+             ----  -------  --------------  ----\n"
+        );
+    }
+
+    #[test]
+    fn truncate_text() {
+        assert_eq!(truncate("name", 4), "name");
+        assert_eq!(truncate("long name", 7), "long...");
+        assert_eq!(truncate("long name", 2), "..");
+    }
+
+    #[test]
+    fn table_with_width() {
+        let mut profile = profile();
+        profile.info.name = "a long name".into();
+        assert_eq!(
+            format_table(&[profile], false, Some(44)).unwrap(),
+            "UUID  EXPIRES     APP IDENTIFIER        NAME\n\
+             ----  ----------  --------------------  ----\n\
+             123   1970-01-02  TEAM.com.example.app  a...\n"
+        );
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = format_json(&[profile()]).unwrap();
+        let output: JsonOutput = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            output,
+            JsonOutput {
+                schema_version: JSON_SCHEMA_VERSION,
+                count: 1,
+                profiles: vec![ProfileJson {
+                    path: "/profiles/1.mobileprovision".into(),
+                    uuid: "123".into(),
+                    name: "name".into(),
+                    app_identifier: "TEAM.com.example.app".into(),
+                    creation_date: "1970-01-01T00:00:00Z".into(),
+                    expiration_date: "1970-01-02T00:00:00Z".into(),
+                }],
+            }
+        );
+    }
+}
@@ -0,0 +1,952 @@
+use assert_cmd::Command;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Writes a profile with `uuid` and `app_identifier` into `dir`.
+fn write_profile(dir: &Path, uuid: &str, app_identifier: &str) {
+    let xml = fs::read_to_string("../lib/tests/test.xml")
+        .unwrap()
+        .replace("fbcdef0a-af78-4a11-b1f1-87cb897bfa8e", uuid)
+        .replace("1234567890.com.testapp", app_identifier);
+    fs::write(dir.join(format!("{}.mobileprovision", uuid)), xml).unwrap();
+}
+
+/// Writes a zip archive with `files` given as pairs of names and contents into `path`.
+fn write_archive(path: &Path, files: &[(&str, &[u8])]) {
+    let mut archive = zip::ZipWriter::new(fs::File::create(path).unwrap());
+    for (name, data) in files {
+        archive
+            .start_file(*name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        archive.write_all(data).unwrap();
+    }
+    archive.finish().unwrap();
+}
+
+fn mprovision() -> Command {
+    Command::cargo_bin("mprovision").unwrap()
+}
+
+#[test]
+fn remove_with_glob() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    write_profile(temp_dir.path(), "2", "TEAM.com.example.widget");
+    write_profile(temp_dir.path(), "3", "TEAM.com.other.app");
+    mprovision()
+        .args([
+            "remove",
+            "com.example.*",
+            "--glob",
+            "--permanently",
+            "--source",
+        ])
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+    let remaining = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(remaining, vec!["3.mobileprovision"]);
+}
+
+#[test]
+fn remove_without_glob_treats_pattern_as_id() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let output = mprovision()
+        .args(["remove", "com.example.*", "--permanently", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .ends_with("Error: Profile not found: com.example.*\n"));
+    assert!(temp_dir.path().join("1.mobileprovision").exists());
+}
+
+#[test]
+fn remove_warns_about_missing_ids() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    write_profile(temp_dir.path(), "2", "TEAM.com.example.widget");
+    mprovision()
+        .args([
+            "remove",
+            "1",
+            "3",
+            "--fail-on-missing",
+            "--permanently",
+            "--source",
+        ])
+        .arg(temp_dir.path())
+        .assert()
+        .failure();
+    assert!(temp_dir.path().join("1.mobileprovision").exists());
+    let output = mprovision()
+        .args(["remove", "1", "3", "--permanently", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .success()
+        .stderr("Warning: no profile found for id '3'\n")
+        .get_output()
+        .stdout
+        .clone();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .ends_with("\nRemoved 1 profile(s).\n"));
+    assert!(!temp_dir.path().join("1.mobileprovision").exists());
+    assert!(temp_dir.path().join("2.mobileprovision").exists());
+}
+
+#[test]
+fn remove_with_strict_ids() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let output = mprovision()
+        .args(["remove", "1", "2", "3", "--strict-ids", "--permanently"])
+        .arg("--source")
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("no profile found for id '2'"));
+    assert!(stderr.contains("no profile found for id '3'"));
+    assert!(temp_dir.path().join("1.mobileprovision").exists());
+}
+
+#[test]
+fn clean_prints_removed_profiles() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let output = mprovision()
+        .env("NO_COLOR", "1")
+        .args(["clean", "--permanently", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("1\nTEAM.com.example.app\n"));
+    assert!(stdout.ends_with("\nRemoved 1 profile(s).\n"));
+    assert!(!temp_dir.path().join("1.mobileprovision").exists());
+}
+
+#[test]
+fn list_warns_about_empty_directory() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    mprovision()
+        .args(["list", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .code(2)
+        .stderr(format!(
+            "Warning: No provisioning profiles found in {}. Have you installed any?\n",
+            temp_dir.path().display()
+        ));
+    mprovision()
+        .args(["list", "--quiet", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .code(2)
+        .stderr("");
+}
+
+#[test]
+fn list_warns_about_missing_default_directory() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let output = mprovision()
+        .env("HOME", temp_dir.path())
+        .env_remove("XDG_DATA_HOME")
+        .arg("list")
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with(&format!(
+            "Default directory does not exist: {}",
+            temp_dir.path().display()
+        )));
+}
+
+#[test]
+fn list_without_matches() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    mprovision()
+        .args(["list", "--text", "widget", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .code(2)
+        .stdout("");
+    mprovision()
+        .args(["list", "--count", "--text", "widget", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .code(2)
+        .stdout("0\n");
+    mprovision()
+        .args(["list", "--text", "app", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+}
+
+#[test]
+fn list_truncates_names_to_columns() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let output = mprovision()
+        .env("COLUMNS", "40")
+        .env("NO_COLOR", "1")
+        .args(["list", "--format", "oneline", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, "1 2020-07-11 TEAM.com.example.app Tes...\n");
+    let output = mprovision()
+        .env_remove("COLUMNS")
+        .env("NO_COLOR", "1")
+        .args(["list", "--format", "oneline", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(!String::from_utf8(output.stdout).unwrap().contains("..."));
+}
+
+#[test]
+fn list_with_progress() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    write_profile(temp_dir.path(), "2", "TEAM.com.example.other");
+    let output = mprovision()
+        .args(["list", "--progress", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("[1/2]"));
+    assert!(stderr.ends_with("[2/2]\n"));
+}
+
+#[test]
+fn list_by_platform() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.ios");
+    let xml = fs::read_to_string(temp_dir.path().join("1.mobileprovision"))
+        .unwrap()
+        .replace("<string>iOS</string>", "<string>OSX</string>")
+        .replace("TEAM.com.example.ios", "TEAM.com.example.mac")
+        .replace("<string>1</string>", "<string>2</string>");
+    fs::write(temp_dir.path().join("2.mobileprovision"), xml).unwrap();
+    let output = mprovision()
+        .args(["list", "--platform", "macos", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("TEAM.com.example.mac"));
+    assert!(!stdout.contains("TEAM.com.example.ios"));
+}
+
+#[test]
+fn list_without_expired() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    // The fixture expires in 2020.
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let output = mprovision()
+        .args(["list", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let output = mprovision()
+        .args(["list", "--no-expired", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("TEAM.com.example.app"));
+}
+
+#[test]
+fn list_grouped_by_date() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let xml = fs::read_to_string(temp_dir.path().join("1.mobileprovision"))
+        .unwrap()
+        .replace("2019-07-12T10:20:02Z", "2019-07-13T10:20:02Z")
+        .replace("<string>1</string>", "<string>2</string>");
+    fs::write(temp_dir.path().join("2.mobileprovision"), xml).unwrap();
+    let output = mprovision()
+        .args(["list", "--group-by-date", "--format", "oneline", "--source"])
+        .arg(temp_dir.path())
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5);
+    assert_eq!(lines[0], "2019-07-12");
+    assert!(lines[1].starts_with("1 "));
+    assert_eq!(lines[3], "2019-07-13");
+    assert!(lines[4].starts_with("2 "));
+    let output = mprovision()
+        .args(["list", "--group-by-date", "--format", "csv", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+}
+
+#[test]
+fn list_sorted_by_env_variable() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "2", "TEAM.com.example.widget");
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let output = mprovision()
+        .env("MPROVISION_SORT_BY", "uuid")
+        .env("NO_COLOR", "1")
+        .args(["list", "--oneline", "--sort-by", "name", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let uuids = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .map(|line| line.split(' ').next().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(uuids, vec!["1", "2"]);
+}
+
+#[test]
+fn list_with_invalid_env_sort_by() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    mprovision()
+        .env("MPROVISION_SORT_BY", "date")
+        .args(["list", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn extract_skips_corrupt_profiles() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let archive = temp_dir.path().join("app.ipa");
+    let xml = fs::read("../lib/tests/test.xml").unwrap();
+    write_archive(
+        &archive,
+        &[
+            ("Payload/App.app/embedded.mobileprovision", &xml),
+            ("Payload/Corrupt.app/embedded.mobileprovision", &xml[..100]),
+        ],
+    );
+    let destination = temp_dir.path().join("profiles");
+    let output = mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(&destination)
+        .arg("--verbose")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.starts_with("Warning: Skipped"));
+    assert!(stderr.contains("Skipped 0 non-profile entries\n"));
+    assert!(stderr.contains("Skipped 1 profile(s) that failed to decode\n"));
+    assert_eq!(fs::read_dir(&destination).unwrap().count(), 1);
+
+    mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(temp_dir.path().join("strict"))
+        .arg("--strict")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn list_count() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    write_profile(temp_dir.path(), "2", "TEAM.com.example.widget");
+    mprovision()
+        .args(["list", "--count", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .success()
+        .stdout("2\n");
+    mprovision()
+        .args(["list", "--count", "--json", "--text", "widget", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .success()
+        .stdout("{\n  \"count\": 1\n}\n");
+}
+
+#[test]
+fn list_paginated() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    for uuid in ["1", "2", "3", "4", "5"] {
+        write_profile(temp_dir.path(), uuid, "TEAM.com.example.app");
+    }
+    let list = |args: &[&str]| {
+        let output = mprovision()
+            .env("NO_COLOR", "1")
+            .args([
+                "list",
+                "--format",
+                "oneline",
+                "--sort-by",
+                "uuid",
+                "--source",
+            ])
+            .arg(temp_dir.path())
+            .args(args)
+            .output()
+            .unwrap();
+        // An empty page exits with code 2.
+        assert!(matches!(output.status.code(), Some(0 | 2)));
+        String::from_utf8(output.stdout)
+            .unwrap()
+            .lines()
+            .map(|line| line.split(' ').next().unwrap().to_owned())
+            .collect::<Vec<_>>()
+    };
+    let all = list(&[]);
+    let mut paginated = list(&["--limit", "2"]);
+    loop {
+        let after = paginated.last().unwrap().clone();
+        let page = list(&["--limit", "2", "--after", &after]);
+        if page.is_empty() {
+            break;
+        }
+        paginated.extend(page);
+    }
+    assert_eq!(all.len(), 5);
+    assert_eq!(paginated, all);
+    mprovision()
+        .args(["list", "--after", "6", "--source"])
+        .arg(temp_dir.path())
+        .assert()
+        .failure();
+}
+
+#[test]
+fn show_by_uuid_or_path() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    let by_uuid = mprovision()
+        .args(["show", "1", "--source"])
+        .arg(temp_dir.path())
+        .output()
+        .unwrap();
+    assert!(by_uuid.status.success());
+    let by_path = mprovision()
+        .arg("show")
+        .arg(temp_dir.path().join("1.mobileprovision"))
+        .output()
+        .unwrap();
+    assert!(by_path.status.success());
+    assert_eq!(by_uuid.stdout, by_path.stdout);
+    assert!(String::from_utf8(by_path.stdout)
+        .unwrap()
+        .contains("TEAM.com.example.app"));
+}
+
+#[test]
+fn show_diff_of_two_profiles() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_profile(temp_dir.path(), "1", "TEAM.com.example.app");
+    write_profile(temp_dir.path(), "2", "TEAM.com.example.other");
+    let output = mprovision()
+        .arg("show-diff")
+        .arg(temp_dir.path().join("1.mobileprovision"))
+        .arg(temp_dir.path().join("2.mobileprovision"))
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("-Entitlements.application-identifier: TEAM.com.example.app\n"));
+    assert!(stdout.contains("+Entitlements.application-identifier: TEAM.com.example.other\n"));
+    assert!(!stdout.contains("Name:"));
+    let output = mprovision()
+        .args(["show-diff", "--context"])
+        .arg(temp_dir.path().join("1.mobileprovision"))
+        .arg(temp_dir.path().join("2.mobileprovision"))
+        .output()
+        .unwrap();
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("\n Name: "));
+}
+
+#[test]
+fn extract_with_output_name_format() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let archive = temp_dir.path().join("app.ipa");
+    let xml = fs::read("../lib/tests/test.xml").unwrap();
+    let names = (0..10)
+        .map(|i| format!("Payload/App{}.app/embedded.mobileprovision", i))
+        .collect::<Vec<_>>();
+    let files = names
+        .iter()
+        .map(|name| (name.as_str(), &xml[..]))
+        .collect::<Vec<_>>();
+    write_archive(&archive, &files);
+    let destination = temp_dir.path().join("profiles");
+    mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(&destination)
+        .args(["--output-name-format", "{index}_{uuid}"])
+        .assert()
+        .success();
+    let mut file_names = fs::read_dir(&destination)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect::<Vec<_>>();
+    file_names.sort();
+    assert_eq!(file_names.len(), 10);
+    assert_eq!(
+        file_names[0],
+        "00_fbcdef0a-af78-4a11-b1f1-87cb897bfa8e.mobileprovision"
+    );
+    assert_eq!(
+        file_names[9],
+        "09_fbcdef0a-af78-4a11-b1f1-87cb897bfa8e.mobileprovision"
+    );
+}
+
+#[test]
+fn extract_and_list_mac_profiles() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let archive = temp_dir.path().join("app.zip");
+    let xml = fs::read("../lib/tests/test.xml").unwrap();
+    write_archive(
+        &archive,
+        &[("App.app/Contents/embedded.provisionprofile", &xml)],
+    );
+    let destination = temp_dir.path().join("profiles");
+    mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(&destination)
+        .arg("--require-profiles")
+        .assert()
+        .failure();
+    mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(&destination)
+        .args(["--require-profiles", "--mac"])
+        .assert()
+        .success();
+    assert!(destination
+        .join("fbcdef0a-af78-4a11-b1f1-87cb897bfa8e.provisionprofile")
+        .exists());
+    mprovision()
+        .args(["list", "--count", "--source"])
+        .arg(&destination)
+        .assert()
+        .code(2)
+        .stdout("0\n");
+    mprovision()
+        .args(["list", "--count", "--mac", "--source"])
+        .arg(&destination)
+        .assert()
+        .success()
+        .stdout("1\n");
+}
+
+#[test]
+fn extract_partially_decoded_profiles() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let archive = temp_dir.path().join("app.ipa");
+    let xml = fs::read_to_string("../lib/tests/test.xml")
+        .unwrap()
+        .replace(
+            "<date>2020-07-11T10:20:02Z</date>",
+            "<string>next year</string>",
+        );
+    write_archive(
+        &archive,
+        &[("Payload/App.app/embedded.mobileprovision", xml.as_bytes())],
+    );
+    let destination = temp_dir.path().join("profiles");
+    let output = mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(&destination)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .ends_with(": invalid field `ExpirationDate`\n"));
+    assert_eq!(fs::read_dir(&destination).unwrap().count(), 1);
+
+    mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(temp_dir.path().join("strict"))
+        .arg("--strict")
+        .assert()
+        .failure();
+}
+
+#[test]
+fn extract_rejects_invalid_uuid() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let xml = fs::read_to_string("../lib/tests/test.xml")
+        .unwrap()
+        .replace("fbcdef0a-af78-4a11-b1f1-87cb897bfa8e", "../../evil");
+    let archive = temp_dir.path().join("app.ipa");
+    write_archive(
+        &archive,
+        &[("Payload/App.app/embedded.mobileprovision", xml.as_bytes())],
+    );
+    let destination = temp_dir.path().join("profiles");
+    let output = mprovision()
+        .arg("extract")
+        .arg(&archive)
+        .arg(&destination)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Invalid profile uuid '../../evil'"));
+    assert_eq!(fs::read_dir(&destination).unwrap().count(), 0);
+    assert!(!temp_dir
+        .path()
+        .with_file_name("evil.mobileprovision")
+        .exists());
+}
+
+#[test]
+fn extract_by_profile_type() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let development = fs::read_to_string("../lib/tests/test.xml").unwrap();
+    let adhoc = development
+        .replace("fbcdef0a-af78-4a11-b1f1-87cb897bfa8e", "ad0c0000")
+        .replace("<key>get-task-allow</key>\n\t\t<true/>", "");
+    let appstore = adhoc.replace("ad0c0000", "a0000000").replace(
+        "<key>ProvisionedDevices</key>\n\t<array>\n\t\t<string>ahhboajfhajdfhvajodhfbknadfljlkgjlajlkal</string>\n\t</array>",
+        "",
+    );
+    let ipa = temp_dir.path().join("app.ipa");
+    write_archive(
+        &ipa,
+        &[
+            (
+                "Payload/App.app/development.mobileprovision",
+                development.as_bytes(),
+            ),
+            ("Payload/App.app/adhoc.mobileprovision", adhoc.as_bytes()),
+            (
+                "Payload/App.app/appstore.mobileprovision",
+                appstore.as_bytes(),
+            ),
+        ],
+    );
+    let destination = temp_dir.path().join("profiles");
+    let output = mprovision()
+        .arg("extract")
+        .arg(&ipa)
+        .arg(&destination)
+        .args(["--profile-type", "adhoc", "--verbose"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("Skipped 2 profile(s) that aren't adhoc\n"));
+    let names = fs::read_dir(&destination)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["ad0c0000.mobileprovision"]);
+    let output = mprovision()
+        .arg("extract")
+        .arg(&ipa)
+        .arg(&destination)
+        .args(["--profile-type", "enterprise", "--require-profiles"])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("No enterprise provisioning profiles found in"));
+}
+
+#[test]
+fn backup_and_restore() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source = temp_dir.path().join("source");
+    fs::create_dir(&source).unwrap();
+    write_profile(&source, "1", "TEAM.com.example.app");
+    write_profile(&source, "2", "TEAM.com.example.other");
+    let archive = temp_dir.path().join("profiles.tar.gz");
+    let output = mprovision()
+        .arg("backup")
+        .arg(&archive)
+        .arg("--source")
+        .arg(&source)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let destination = temp_dir.path().join("destination");
+    let output = mprovision()
+        .arg("restore")
+        .arg(&archive)
+        .arg("--destination")
+        .arg(&destination)
+        .arg("--verify")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .starts_with("Restored 2 profile(s)"));
+    for name in ["1.mobileprovision", "2.mobileprovision"] {
+        assert_eq!(
+            fs::read(destination.join(name)).unwrap(),
+            fs::read(source.join(name)).unwrap()
+        );
+    }
+}
+
+#[test]
+fn validate_continues_after_unreadable_file() {
+    let output = mprovision()
+        .args([
+            "validate",
+            "nonexistent.mobileprovision",
+            "../lib/tests/signed.mobileprovision",
+        ])
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("nonexistent.mobileprovision: "));
+    assert!(stderr.contains("signed.mobileprovision: "));
+}
+
+#[test]
+fn install_from_base64() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = tempfile::tempdir().unwrap();
+    let profile = source_dir.path().join("profile.mobileprovision");
+    fs::copy("../lib/tests/test.xml", &profile).unwrap();
+    let output = mprovision()
+        .args(["show-file", "--base64"])
+        .arg(&profile)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout.clone())
+        .unwrap()
+        .lines()
+        .all(|line| line.len() <= 76));
+    let output = mprovision()
+        .args(["install", "--base64", "--destination"])
+        .arg(temp_dir.path())
+        .write_stdin(output.stdout)
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let installed = fs::read_dir(temp_dir.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        installed,
+        vec![temp_dir
+            .path()
+            .join("fbcdef0a-af78-4a11-b1f1-87cb897bfa8e.mobileprovision")]
+    );
+    assert_eq!(
+        fs::read(&installed[0]).unwrap(),
+        fs::read(&profile).unwrap()
+    );
+}
+
+#[test]
+fn install_with_extension_by_platform() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let source_dir = tempfile::tempdir().unwrap();
+    let xml = fs::read_to_string("../lib/tests/test.xml").unwrap();
+    let profile = source_dir.path().join("profile.mobileprovision");
+    fs::write(
+        &profile,
+        xml.replace("<string>iOS</string>", "<string>OSX</string>"),
+    )
+    .unwrap();
+    mprovision()
+        .arg("install")
+        .arg(&profile)
+        .arg("--destination")
+        .arg(temp_dir.path())
+        .assert()
+        .success();
+    assert!(temp_dir
+        .path()
+        .join("fbcdef0a-af78-4a11-b1f1-87cb897bfa8e.provisionprofile")
+        .exists());
+}
+
+#[test]
+fn install_rejects_invalid_uuid() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let xml = fs::read_to_string("../lib/tests/test.xml").unwrap();
+    for uuid in ["../../evil", "not-a-uuid"] {
+        let path = temp_dir.path().join("profile.mobileprovision");
+        fs::write(
+            &path,
+            xml.replace("fbcdef0a-af78-4a11-b1f1-87cb897bfa8e", uuid),
+        )
+        .unwrap();
+        let destination = temp_dir.path().join("profiles");
+        let output = mprovision()
+            .arg("install")
+            .arg(&path)
+            .arg("--destination")
+            .arg(&destination)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("Invalid profile uuid"));
+        assert!(!destination.exists());
+        assert!(!temp_dir
+            .path()
+            .with_file_name("evil.mobileprovision")
+            .exists());
+    }
+}
+
+#[test]
+fn extract_encrypted_archive() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let destination = temp_dir.path().join("profiles");
+    mprovision()
+        .args(["extract", "tests/encrypted.ipa"])
+        .arg(&destination)
+        .args(["--password", "secret"])
+        .assert()
+        .success();
+    assert_eq!(fs::read_dir(&destination).unwrap().count(), 1);
+
+    for args in [&["--password", "wrong"][..], &[]] {
+        let output = mprovision()
+            .args(["extract", "tests/encrypted.ipa"])
+            .arg(temp_dir.path().join("failed"))
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(!output.status.success());
+        assert!(String::from_utf8(output.stderr)
+            .unwrap()
+            .contains("encrypted.ipa"));
+    }
+}
+
+#[test]
+fn list_from_several_sources() {
+    let temp_dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+    write_profile(temp_dirs[0].path(), "1", "TEAM.com.example.app");
+    write_profile(temp_dirs[1].path(), "1", "TEAM.com.example.app");
+    write_profile(temp_dirs[1].path(), "2", "TEAM.com.example.widget");
+    let sources = format!(
+        "{}:{}",
+        temp_dirs[0].path().display(),
+        temp_dirs[1].path().display()
+    );
+    mprovision()
+        .args(["list", "--count", "--source", &sources])
+        .assert()
+        .success()
+        .stdout("2\n");
+}
+
+#[test]
+fn version() {
+    mprovision()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(format!("mprovision {}\n", env!("CARGO_PKG_VERSION")));
+    let output = mprovision().arg("--version-json").output().unwrap();
+    assert!(output.status.success());
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["version"], env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        json["build_date"].as_str().unwrap().len(),
+        "YYYY-MM-DD".len()
+    );
+}
+
+/// Serves `data` at `/profile.mobileprovision` and `404` at other paths, returns
+/// the url of the server.
+#[cfg(feature = "http")]
+fn serve(data: Vec<u8>) -> String {
+    use std::io::{BufRead, BufReader};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    std::thread::spawn(move || {
+        for mut stream in listener.incoming().map_while(Result::ok) {
+            let mut lines = BufReader::new(&stream).lines().map_while(Result::ok);
+            let found = lines
+                .next()
+                .is_some_and(|line| line.starts_with("GET /profile.mobileprovision "));
+            lines.take_while(|line| !line.is_empty()).for_each(drop);
+            let (status, body) = if found {
+                ("200 OK", data.as_slice())
+            } else {
+                ("404 Not Found", &[][..])
+            };
+            let head = format!(
+                "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                status,
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes());
+            let _ = stream.write_all(body);
+        }
+    });
+    url
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn show_url() {
+    let xml = fs::read_to_string("../lib/tests/test.xml").unwrap();
+    let url = serve(xml.clone().into_bytes());
+    let output = mprovision()
+        .args(["show", "--url", &format!("{}/profile.mobileprovision", url)])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap().trim(), xml.trim());
+    mprovision()
+        .args(["show", "--url", &format!("{}/missing.mobileprovision", url)])
+        .assert()
+        .failure();
+}
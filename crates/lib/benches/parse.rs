@@ -0,0 +1,49 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use mprovision::profile::{Info, Profile};
+use std::fs;
+use std::path::PathBuf;
+
+/// Number of profiles parsed in every iteration.
+const PROFILES_COUNT: usize = 100;
+
+fn parse(c: &mut Criterion) {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let data = include_bytes!("../tests/signed.mobileprovision");
+    let file_paths = (0..PROFILES_COUNT)
+        .map(|i| {
+            let path = temp_dir.path().join(format!("{}.mobileprovision", i));
+            fs::write(&path, data).unwrap();
+            path
+        })
+        .collect::<Vec<PathBuf>>();
+
+    let mut group = c.benchmark_group("parse");
+    group.throughput(Throughput::Bytes((data.len() * PROFILES_COUNT) as u64));
+    group.bench_function(BenchmarkId::new("from_file", "single-threaded"), |b| {
+        b.iter(|| {
+            for path in &file_paths {
+                Profile::from_file(path).unwrap();
+            }
+        })
+    });
+    group.bench_function(BenchmarkId::new("from_file", "parallel"), |b| {
+        b.iter(|| mprovision::filter(file_paths.clone(), |_| true))
+    });
+    group.bench_function(BenchmarkId::new("from_file", "parallel-dir"), |b| {
+        b.iter(|| mprovision::filter_dir(temp_dir.path(), |_| true).unwrap())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("parse_data");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("from_xml_data", |b| {
+        b.iter(|| Info::from_xml_data(data).unwrap())
+    });
+    group.bench_function("from_xml_data_minimal", |b| {
+        b.iter(|| Info::from_xml_data_minimal(data).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
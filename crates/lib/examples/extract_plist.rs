@@ -0,0 +1,23 @@
+//! Prints a plist content embedded in a file.
+//!
+//! Usage: `cargo run --example extract_plist -- <FILE>`
+
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
+
+fn main() -> io::Result<()> {
+    let Some(path) = env::args_os().nth(1) else {
+        eprintln!("Usage: extract_plist <FILE>");
+        process::exit(2);
+    };
+    let data = fs::read(path)?;
+    match mprovision::find_plist_in_data(&data) {
+        Some(plist) => io::stdout().write_all(plist),
+        None => {
+            eprintln!("No plist content found");
+            process::exit(1);
+        }
+    }
+}
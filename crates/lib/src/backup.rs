@@ -0,0 +1,266 @@
+//! Backups of provisioning profiles as `.tar.gz` archives.
+//!
+//! An archive contains profiles by their file names and a `manifest.json` file
+//! that lists uuids, names and SHA-256 hashes of the profiles, so their integrity
+//! can be verified on restore.
+
+use crate::error::Error;
+use crate::profile::Info;
+use crate::{sha256, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::Path;
+
+/// A name of the manifest file in a backup archive.
+pub const MANIFEST_FILE_NAME: &str = "manifest.json";
+
+/// Represents a provisioning profile listed in the manifest of a backup archive.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// A file name of the profile in the archive.
+    pub file_name: String,
+    /// A uuid of the profile, empty if the profile couldn't be parsed.
+    pub uuid: String,
+    /// A name of the profile, empty if the profile couldn't be parsed.
+    pub name: String,
+    /// A SHA-256 hash of the profile as a lowercase hex string.
+    pub sha256: String,
+}
+
+/// Writes provisioning profiles of a `dir` into a gzipped tar archive at
+/// `archive_path`, see [`crate::file_paths_all`].
+///
+/// Returns entries of the manifest written into the archive. Profiles that can't
+/// be parsed are backed up as is, so a broken profile doesn't prevent backups.
+///
+/// # Errors
+/// This function will return an error if a profile can't be read or the
+/// archive can't be written.
+pub fn backup(dir: &Path, archive_path: &Path) -> Result<Vec<ManifestEntry>> {
+    let mut file_paths = crate::file_paths_all(dir)?.collect::<Vec<_>>();
+    file_paths.sort();
+    let file = File::create(archive_path).map_err(Error::with_path(archive_path))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+    let mut manifest = Vec::with_capacity(file_paths.len());
+    for file_path in file_paths {
+        let data = fs::read(&file_path).map_err(Error::with_path(&file_path))?;
+        let (uuid, name) = Info::from_xml_data_minimal(&data)
+            .map(|info| (info.uuid, info.name))
+            .unwrap_or_default();
+        let Some(file_name) = file_path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        append(&mut builder, file_name, &data)?;
+        manifest.push(ManifestEntry {
+            file_name: file_name.to_owned(),
+            uuid,
+            name,
+            sha256: sha256(&data),
+        });
+    }
+    let json = serde_json::to_vec_pretty(&manifest)
+        .map_err(|err| Error::Own(format!("Couldn't write manifest: {}", err)))?;
+    append(&mut builder, MANIFEST_FILE_NAME, &json)?;
+    builder.into_inner()?.finish()?;
+    Ok(manifest)
+}
+
+/// Writes provisioning profiles listed in the manifest of a backup archive at
+/// `archive_path` into a `destination` directory.
+///
+/// Returns entries of the manifest. If `verify` is `true` no profiles are written
+/// when a hash of any of them doesn't match the manifest.
+///
+/// # Errors
+/// This function will return an error if the archive doesn't have a manifest or
+/// a profile listed in it, or the manifest lists a file name that isn't plain.
+pub fn restore(
+    archive_path: &Path,
+    destination: &Path,
+    verify: bool,
+) -> Result<Vec<ManifestEntry>> {
+    let manifest: Vec<ManifestEntry> =
+        read_entries(archive_path, |name| name == MANIFEST_FILE_NAME)?
+            .remove(MANIFEST_FILE_NAME)
+            .ok_or_else(|| Error::Own(format!("'{}' is missing", MANIFEST_FILE_NAME)))
+            .and_then(|json| {
+                serde_json::from_slice(&json)
+                    .map_err(|err| Error::Own(format!("Invalid {}: {}", MANIFEST_FILE_NAME, err)))
+            })?;
+    // Only plain file names are accepted, so profiles can't be written outside
+    // of the destination.
+    if let Some(entry) = manifest
+        .iter()
+        .find(|entry| Path::new(&entry.file_name).file_name() != Some(entry.file_name.as_ref()))
+    {
+        return Err(Error::Own(format!(
+            "Invalid file name '{}' in {}",
+            entry.file_name, MANIFEST_FILE_NAME
+        )));
+    }
+    let files = read_entries(archive_path, |name| {
+        manifest.iter().any(|entry| entry.file_name == name)
+    })?;
+    for entry in &manifest {
+        let Some(data) = files.get(&entry.file_name) else {
+            return Err(Error::Own(format!("'{}' is missing", entry.file_name)));
+        };
+        if verify && sha256(data) != entry.sha256 {
+            return Err(Error::Own(format!(
+                "Hash of '{}' doesn't match the manifest",
+                entry.file_name
+            )));
+        }
+    }
+    fs::create_dir_all(destination).map_err(Error::with_path(destination))?;
+    for entry in &manifest {
+        let path = destination.join(&entry.file_name);
+        fs::write(&path, &files[&entry.file_name]).map_err(Error::with_path(&path))?;
+    }
+    Ok(manifest)
+}
+
+/// Reads entries of an archive at `archive_path` whose names satisfy the
+/// `predicate`, other entries aren't kept in memory.
+fn read_entries(
+    archive_path: &Path,
+    predicate: impl Fn(&str) -> bool,
+) -> Result<HashMap<String, Vec<u8>>> {
+    let file = File::open(archive_path).map_err(Error::with_path(archive_path))?;
+    let mut archive = tar::Archive::new(GzDecoder::new(file));
+    let mut files = HashMap::new();
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let Some(name) = entry.path()?.to_str().map(str::to_owned) else {
+            continue;
+        };
+        if !predicate(&name) {
+            continue;
+        }
+        let mut data = Vec::new();
+        entry.read_to_end(&mut data)?;
+        files.insert(name, data);
+    }
+    Ok(files)
+}
+
+/// Appends a file with a `name` and `data` to an archive.
+fn append<W: std::io::Write>(builder: &mut tar::Builder<W>, name: &str, data: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(data.len() as u64);
+    header.set_mode(0o644);
+    builder.append_data(&mut header, name, data)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backup_and_restore() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir(&source).unwrap();
+        let data = include_bytes!("../tests/test.xml");
+        fs::write(source.join("1.mobileprovision"), data).unwrap();
+        let archive_path = temp_dir.path().join("backup.tar.gz");
+        let manifest = backup(&source, &archive_path).unwrap();
+        assert_eq!(
+            manifest,
+            vec![ManifestEntry {
+                file_name: "1.mobileprovision".into(),
+                uuid: "fbcdef0a-af78-4a11-b1f1-87cb897bfa8e".into(),
+                name: "TestApp iOS Development".into(),
+                sha256: sha256(data),
+            }]
+        );
+        let destination = temp_dir.path().join("destination");
+        assert_eq!(
+            restore(&archive_path, &destination, true).unwrap(),
+            manifest
+        );
+        assert_eq!(
+            fs::read(destination.join("1.mobileprovision")).unwrap(),
+            data
+        );
+    }
+
+    #[test]
+    fn backup_unparseable_profile() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let source = temp_dir.path().join("source");
+        fs::create_dir(&source).unwrap();
+        fs::write(source.join("1.mobileprovision"), b"corrupt").unwrap();
+        let archive_path = temp_dir.path().join("backup.tar.gz");
+        let manifest = backup(&source, &archive_path).unwrap();
+        assert_eq!(
+            manifest,
+            vec![ManifestEntry {
+                file_name: "1.mobileprovision".into(),
+                uuid: String::new(),
+                name: String::new(),
+                sha256: sha256(b"corrupt"),
+            }]
+        );
+        let destination = temp_dir.path().join("destination");
+        restore(&archive_path, &destination, true).unwrap();
+        assert_eq!(
+            fs::read(destination.join("1.mobileprovision")).unwrap(),
+            b"corrupt"
+        );
+    }
+
+    #[test]
+    fn restore_with_hash_mismatch() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive_path = temp_dir.path().join("backup.tar.gz");
+        let manifest = vec![ManifestEntry {
+            file_name: "1.mobileprovision".into(),
+            uuid: "1".into(),
+            name: "name".into(),
+            sha256: sha256(b"original"),
+        }];
+        let file = File::create(&archive_path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        append(&mut builder, "1.mobileprovision", b"tampered").unwrap();
+        let json = serde_json::to_vec(&manifest).unwrap();
+        append(&mut builder, MANIFEST_FILE_NAME, &json).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        let destination = temp_dir.path().join("destination");
+        assert!(restore(&archive_path, &destination, true).is_err());
+        assert!(!destination.exists());
+        assert_eq!(
+            restore(&archive_path, &destination, false).unwrap(),
+            manifest
+        );
+    }
+
+    #[test]
+    fn restore_with_invalid_file_name() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let archive_path = temp_dir.path().join("backup.tar.gz");
+        let manifest = vec![ManifestEntry {
+            file_name: "../evil.mobileprovision".into(),
+            uuid: "1".into(),
+            name: "name".into(),
+            sha256: sha256(b"evil"),
+        }];
+        let file = File::create(&archive_path).unwrap();
+        let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+        append(&mut builder, "evil.mobileprovision", b"evil").unwrap();
+        let json = serde_json::to_vec(&manifest).unwrap();
+        append(&mut builder, MANIFEST_FILE_NAME, &json).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+        let destination = temp_dir.path().join("destination");
+        let err = restore(&archive_path, &destination, false).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid file name"));
+        assert!(!destination.exists());
+        assert!(!temp_dir.path().join("evil.mobileprovision").exists());
+    }
+}
@@ -1,6 +1,8 @@
+use crate::profile::InfoError;
 use std::error;
 use std::fmt;
 use std::io;
+use std::path::{Path, PathBuf};
 use std::string::FromUtf8Error;
 
 /// An Error type.
@@ -8,15 +10,30 @@ use std::string::FromUtf8Error;
 pub enum Error {
     /// Denotes I/O error.
     Io(io::Error),
+    /// Denotes I/O error of a file or a directory at `path`.
+    FileIo { path: PathBuf, source: io::Error },
     /// Denotes error that produces this crate.
     Own(String),
+    /// Denotes that a profile is not found.
+    NotFound(String),
+    /// Denotes error that produces an external crate.
+    External(Box<dyn error::Error + Send + Sync>),
+    /// Denotes error of reading a zip archive.
+    Zip(zip::result::ZipError),
+    /// Denotes that a profile at `path` couldn't be parsed.
+    Parse { path: PathBuf, source: InfoError },
+    /// Denotes that a profile downloaded from `url` couldn't be parsed.
+    ParseUrl { url: String, source: InfoError },
 }
 
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
         match self {
-            Self::Io(e) => Some(e),
-            Self::Own(_) => None,
+            Self::Io(e) | Self::FileIo { source: e, .. } => Some(e),
+            Self::Own(_) | Self::NotFound(_) => None,
+            Self::Parse { source, .. } | Self::ParseUrl { source, .. } => Some(source),
+            Self::External(e) => Some(e.as_ref()),
+            Self::Zip(e) => Some(e),
         }
     }
 }
@@ -25,7 +42,27 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Io(e) => e.fmt(f),
+            Self::FileIo { path, source } => write!(f, "{}: {}", source, path.display()),
             Self::Own(e) => e.fmt(f),
+            Self::NotFound(e) => write!(f, "Profile not found: {}", e),
+            Self::External(e) => e.fmt(f),
+            Self::Zip(e) => write!(f, "Zip archive error: {}", e),
+            Self::Parse { path, source } => {
+                write!(f, "Couldn't parse '{}': {}", path.display(), source)
+            }
+            Self::ParseUrl { url, source } => write!(f, "Couldn't parse '{}': {}", url, source),
+        }
+    }
+}
+
+impl Error {
+    /// Returns a function that wraps an I/O error with a `path` it occurred at.
+    ///
+    /// Handy to use with `map_err`.
+    pub fn with_path(path: &Path) -> impl FnOnce(io::Error) -> Self + '_ {
+        move |source| Self::FileIo {
+            path: path.to_owned(),
+            source,
         }
     }
 }
@@ -41,3 +78,75 @@ impl From<FromUtf8Error> for Error {
         Self::Own(e.to_string())
     }
 }
+
+impl From<trash::Error> for Error {
+    fn from(e: trash::Error) -> Self {
+        Self::External(Box::new(e))
+    }
+}
+
+impl From<quick_xml::Error> for Error {
+    fn from(e: quick_xml::Error) -> Self {
+        Self::External(Box::new(e))
+    }
+}
+
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Self {
+        Self::Zip(e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn external_error_has_source() {
+        let error = Error::from(trash::Error::Unknown {
+            description: "unknown".to_owned(),
+        });
+        assert!(error.source().is_some());
+        assert_eq!(error.to_string(), error.source().unwrap().to_string());
+    }
+
+    #[test]
+    fn zip_error() {
+        let error = Error::from(zip::result::ZipError::FileNotFound);
+        assert!(matches!(error, Error::Zip(_)));
+        assert!(error.source().is_some());
+        assert_eq!(
+            error.to_string(),
+            "Zip archive error: specified file not found in archive"
+        );
+    }
+
+    #[test]
+    fn file_io_error_has_path() {
+        let path = Path::new("/path/to/foo.mobileprovision");
+        let error = Error::with_path(path)(io::Error::from(io::ErrorKind::NotFound));
+        assert!(error.source().is_some());
+        assert!(error
+            .to_string()
+            .ends_with(": /path/to/foo.mobileprovision"));
+    }
+
+    #[test]
+    fn parse_url_error_has_url() {
+        let error = Error::ParseUrl {
+            url: "https://example.com/1.mobileprovision".to_owned(),
+            source: InfoError::NoPlistFound,
+        };
+        assert!(error.source().is_some());
+        assert_eq!(
+            error.to_string(),
+            "Couldn't parse 'https://example.com/1.mobileprovision': Couldn't find plist content."
+        );
+    }
+
+    #[test]
+    fn own_error_has_no_source() {
+        assert!(Error::Own("error".to_owned()).source().is_none());
+    }
+}
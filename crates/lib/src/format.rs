@@ -0,0 +1,83 @@
+//! Output formats of a list of provisioning profiles.
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Represents a format to output a list of provisioning profiles.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum OutputFormat {
+    /// Every profile takes several lines.
+    #[default]
+    Multiline,
+    /// Every profile takes one line.
+    Oneline,
+    /// A json document.
+    Json,
+    /// Comma separated values with a header.
+    Csv,
+    /// A table with aligned columns.
+    Table,
+}
+
+impl OutputFormat {
+    /// All output formats.
+    pub const ALL: &'static [Self] = &[
+        Self::Multiline,
+        Self::Oneline,
+        Self::Json,
+        Self::Csv,
+        Self::Table,
+    ];
+
+    /// Returns a name of the format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Multiline => "multiline",
+            Self::Oneline => "oneline",
+            Self::Json => "json",
+            Self::Csv => "csv",
+            Self::Table => "table",
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|format| format.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names = Self::ALL
+                    .iter()
+                    .map(|format| format.as_str())
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown format '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_formats() {
+        for format in OutputFormat::ALL {
+            assert_eq!(format.to_string().parse::<OutputFormat>(), Ok(*format));
+        }
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+}
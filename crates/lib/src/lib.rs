@@ -2,16 +2,26 @@
 //! files. Main purpose of this crate is to contain functions and types
 //! for **mprovision**.
 
+use std::collections::{BTreeMap, HashSet};
+use std::ffi::OsString;
 use std::fs::{self, File};
-use std::io::Read;
+use std::io::{self, Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 
 use crate::error::Error;
-use crate::profile::Profile;
+use crate::profile::{Info, InfoError, Profile};
 
+pub mod backup;
 pub mod error;
+pub mod format;
 pub mod plist_extractor;
 pub mod profile;
+pub mod signature;
+pub mod validation;
+
+pub use crate::plist_extractor::find as find_plist_in_data;
 
 /// A Result type for this crate.
 pub type Result<T> = std::result::Result<T, Error>;
@@ -19,9 +29,101 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// A file extension of a povisioning profile.
 pub const EXT_MOBILEPROVISION: &str = "mobileprovision";
 
+/// A file extension of a macOS povisioning profile.
+pub const EXT_PROVISIONPROFILE: &str = "provisionprofile";
+
+/// Returns instance of the `Info` parsed from a `data`.
+///
+/// The same as [`Info::from_xml_data`].
+pub fn info_from_xml_data(data: &[u8]) -> std::result::Result<Info, InfoError> {
+    Info::from_xml_data(data)
+}
+
+/// Downloads a provisioning profile from an `url` and returns instance of the
+/// `Info` parsed from it.
+///
+/// # Errors
+/// This function will return an error if the request fails, the server doesn't
+/// respond with a success status or the profile can't be parsed.
+#[cfg(feature = "http")]
+pub async fn info_from_url(url: &str) -> Result<Info> {
+    Info::from_xml_data(&download(url).await?).map_err(|source| Error::ParseUrl {
+        url: url.to_owned(),
+        source,
+    })
+}
+
+/// Downloads a provisioning profile from an `url` and returns its xml content.
+///
+/// The same as [`show`] but for a profile on an HTTP(S) server.
+///
+/// # Errors
+/// The same as for [`info_from_url`].
+#[cfg(feature = "http")]
+pub async fn show_url(url: &str) -> Result<String> {
+    let data = download(url).await?;
+    let xml = plist_extractor::find(&data).ok_or_else(|| Error::ParseUrl {
+        url: url.to_owned(),
+        source: InfoError::NoPlistFound,
+    })?;
+    Ok(String::from_utf8(xml.to_owned())?)
+}
+
+#[cfg(feature = "http")]
+async fn download(url: &str) -> Result<Vec<u8>> {
+    let external = |err: reqwest::Error| Error::External(Box::new(err));
+    let data = reqwest::get(url)
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(external)?
+        .bytes()
+        .await
+        .map_err(external)?;
+    Ok(data.to_vec())
+}
+
+/// Returns a SHA-256 hash of `data` as a lowercase hex string.
+pub(crate) fn sha256(data: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(data)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Returns instance of the `Profile` parsed from a file.
+///
+/// The same as [`Profile::from_file`].
+pub fn profile_from_file(path: &Path) -> Result<Profile> {
+    Profile::from_file(path)
+}
+
 /// Returns true if the `file_path` is a provisioning profile file.
+///
+/// The extension is compared case-insensitively regardless of a filesystem.
 pub fn is_mobileprovision(file_path: &Path) -> bool {
-    file_path.extension().and_then(|ext| ext.to_str()) == Some(EXT_MOBILEPROVISION)
+    has_extension(file_path, EXT_MOBILEPROVISION)
+}
+
+/// Returns true if the `file_path` is a macOS provisioning profile file.
+///
+/// The extension is compared case-insensitively regardless of a filesystem.
+pub fn is_provisionprofile(file_path: &Path) -> bool {
+    has_extension(file_path, EXT_PROVISIONPROFILE)
+}
+
+/// Returns true if the `file_path` is either an iOS or a macOS provisioning
+/// profile file.
+pub fn is_any_provision_profile(file_path: &Path) -> bool {
+    is_mobileprovision(file_path) || is_provisionprofile(file_path)
+}
+
+fn has_extension(file_path: &Path, extension: &str) -> bool {
+    file_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
 }
 
 /// Returns an iterator over the `*.mobileprovision` file paths within a given
@@ -34,77 +136,487 @@ pub fn is_mobileprovision(file_path: &Path) -> bool {
 /// - there is no entry in the filesystem at the provided path
 /// - the provided path is not a directory
 pub fn file_paths(dir: &Path) -> Result<impl Iterator<Item = PathBuf>> {
-    let filtered = fs::read_dir(dir)?
-        .filter(|entry| {
-            entry
-                .as_ref()
-                .ok()
-                .map(|entry| is_mobileprovision(entry.path().as_ref()))
-                .unwrap_or(false)
-        })
+    file_paths_matching(dir, is_mobileprovision)
+}
+
+/// Returns an iterator over the `*.mobileprovision` and `*.provisionprofile`
+/// file paths within a given directory.
+///
+/// # Errors
+/// The same as for [`file_paths`].
+pub fn file_paths_all(dir: &Path) -> Result<impl Iterator<Item = PathBuf>> {
+    file_paths_matching(dir, is_any_provision_profile)
+}
+
+fn file_paths_matching(
+    dir: &Path,
+    is_profile: fn(&Path) -> bool,
+) -> Result<impl Iterator<Item = PathBuf>> {
+    let filtered = fs::read_dir(dir)
+        .map_err(Error::with_path(dir))?
         .filter_map(std::result::Result::ok)
-        .map(|entry| entry.path());
+        .map(|entry| entry.path())
+        .filter(move |path| is_profile(path));
+    Ok(filtered)
+}
+
+/// Returns the `*.mobileprovision` file paths within a given directory sorted
+/// lexicographically.
+///
+/// Unlike [`file_paths`] the order doesn't depend on a filesystem, so it's the
+/// same across platforms.
+///
+/// # Errors
+/// The same as for [`file_paths`].
+pub fn file_paths_sorted(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut file_paths = file_paths(dir)?.collect::<Vec<_>>();
+    file_paths.sort();
+    Ok(file_paths)
+}
+
+/// Returns an iterator over the `*.mobileprovision` file paths within a given
+/// directory that can be opened for reading by the current user.
+///
+/// Note that a file is checked at the time of iteration, so it still may become
+/// unreadable before it's actually read. This race is acceptable since a failure
+/// to read a profile is handled by the caller anyway.
+///
+/// # Errors
+/// The same as for [`file_paths`].
+pub fn file_paths_readable(dir: &Path) -> Result<impl Iterator<Item = PathBuf>> {
+    Ok(file_paths(dir)?.filter(|path| File::open(path).is_ok()))
+}
+
+/// Returns an iterator over the `*.mobileprovision` file paths within a given
+/// directory including symlinks that point to provisioning profile files.
+///
+/// Symlinks are followed one level, so a symlink is included if its target has
+/// the `mobileprovision` extension and is a file. Paths of symlinks are returned
+/// as is, not their targets.
+///
+/// # Errors
+/// The same as for [`file_paths`].
+pub fn file_paths_follow_symlinks(dir: &Path) -> Result<impl Iterator<Item = PathBuf>> {
+    let filtered = fs::read_dir(dir)
+        .map_err(Error::with_path(dir))?
+        .filter_map(std::result::Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let target = fs::read_link(path).unwrap_or_else(|_| path.clone());
+            is_mobileprovision(path) || is_mobileprovision(&target)
+        })
+        .filter(|path| {
+            fs::metadata(path)
+                .map(|metadata| metadata.is_file())
+                .unwrap_or(false)
+        });
     Ok(filtered)
 }
 
 /// Returns the path to the directory that contains installed mobile
 /// provisioning profiles.
 ///
-/// Should return `~/Library/MobileDevice/Provisioning Profiles` directory.
+/// On macOS it's `~/Library/MobileDevice/Provisioning Profiles` directory. Other
+/// platforms don't have such a directory, so it's `$XDG_DATA_HOME/mprovision/profiles`
+/// or `~/.local/share/mprovision/profiles` if `XDG_DATA_HOME` isn't set, e.g.
+/// to keep profiles on Linux CI agents.
 ///
 /// # Errors
 /// This function will return an error if 'HOME' environment variable is not set
 /// or equal to the empty string.
 pub fn directory() -> Result<PathBuf> {
-    dirs::home_dir()
-        .map(|path| path.join("Library/MobileDevice/Provisioning Profiles"))
-        .ok_or_else(|| {
-            Error::Own(
-                "'HOME' environment variable is not set or equal to the empty string.".to_owned(),
-            )
-        })
+    directory_in(dirs::home_dir(), std::env::var_os("XDG_DATA_HOME"))
+}
+
+/// Returns the default [`directory`] for given `home` and `xdg_data_home`
+/// directories.
+fn directory_in(home: Option<PathBuf>, xdg_data_home: Option<OsString>) -> Result<PathBuf> {
+    if let Some(dir) = xdg_data_home.filter(|dir| !cfg!(target_os = "macos") && !dir.is_empty()) {
+        return Ok(PathBuf::from(dir).join("mprovision/profiles"));
+    }
+    home.map(|path| {
+        if cfg!(target_os = "macos") {
+            path.join("Library/MobileDevice/Provisioning Profiles")
+        } else {
+            path.join(".local/share/mprovision/profiles")
+        }
+    })
+    .ok_or_else(|| {
+        Error::Own(
+            "'HOME' environment variable is not set or equal to the empty string.".to_owned(),
+        )
+    })
 }
 
 /// Returns `dir` or default [`directory`].
 ///
+/// The default directory is returned even if it doesn't exist, so the caller
+/// can warn about it or get an error with context on access.
+///
 /// # Errors
 /// The same as for [`directory`].
 pub fn dir_or_default(dir: Option<PathBuf>) -> Result<PathBuf> {
-    dir.map(Result::Ok).unwrap_or_else(directory)
+    dir.map_or_else(directory, Ok)
+}
+
+/// Loads profiles from paths.
+pub trait ProfileLoader: Send + Sync {
+    /// Returns a profile loaded from `path`.
+    fn load(&self, path: &Path) -> Result<Profile>;
+}
+
+/// Loads profiles from files using [`Profile::from_file`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FileSystemLoader;
+
+impl ProfileLoader for FileSystemLoader {
+    fn load(&self, path: &Path) -> Result<Profile> {
+        Info::from_file(path).map(|info| Profile {
+            path: path.to_owned(),
+            info,
+        })
+    }
 }
 
 /// Filters files using predicate function `f`.
 ///
-/// The filtering is performed concurrently.
-pub fn filter<F>(file_paths: Vec<PathBuf>, f: F) -> Vec<Profile>
+/// The filtering is performed concurrently, `file_paths` are consumed as they
+/// are needed, so there is no need to collect them beforehand. Profiles are in
+/// the order of `file_paths`.
+pub fn filter<I, F>(file_paths: I, f: F) -> Vec<Profile>
 where
+    I: IntoIterator<Item = PathBuf> + Send,
+    I::IntoIter: Send,
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    filter_with_loader(file_paths, &FileSystemLoader, f)
+}
+
+/// Filters profiles loaded by `loader` using predicate function `f`.
+///
+/// The filtering is performed concurrently. Profiles are in the order of `file_paths`.
+pub fn filter_with_loader<I, L, F>(file_paths: I, loader: &L, f: F) -> Vec<Profile>
+where
+    I: IntoIterator<Item = PathBuf> + Send,
+    I::IntoIter: Send,
+    L: ProfileLoader + ?Sized,
     F: Fn(&Profile) -> bool + Send + Sync,
 {
     use rayon::prelude::*;
+    let mut profiles = file_paths
+        .into_iter()
+        .enumerate()
+        .par_bridge()
+        .filter_map(|(i, path)| loader.load(&path).ok().map(|profile| (i, profile)))
+        .filter(|(_, profile)| f(profile))
+        .collect::<Vec<_>>();
+    // `par_bridge` doesn't preserve the order of items.
+    profiles.sort_unstable_by_key(|(i, _)| *i);
+    profiles.into_iter().map(|(_, profile)| profile).collect()
+}
+
+/// Filters files using predicate function `f` reading at most `concurrency`
+/// files at the same time.
+///
+/// The `concurrency` equal to `0` means to use all available cores, the same
+/// as [`filter`] does.
+pub fn filter_with_concurrency<I, F>(file_paths: I, concurrency: usize, f: F) -> Vec<Profile>
+where
+    I: IntoIterator<Item = PathBuf> + Send,
+    I::IntoIter: Send,
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    install(concurrency, || filter(file_paths, f))
+}
+
+/// Filters profiles loaded by `loader` from already collected `file_paths` using
+/// predicate function `f`.
+///
+/// Unlike [`filter_with_loader`] the number of files is known beforehand, so
+/// the work is split evenly across threads and the order is preserved for free.
+fn filter_collected<L, F>(file_paths: &[PathBuf], loader: &L, f: F) -> Vec<Profile>
+where
+    L: ProfileLoader + ?Sized,
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    filter_collected_until(file_paths, loader, f, &AtomicBool::new(false), &|_, _| {})
+}
+
+/// The same as [`filter_collected`] but skips the rest of `file_paths` once
+/// `cancelled` is `true` and calls `progress` after each file is read.
+fn filter_collected_until<L, F, P>(
+    file_paths: &[PathBuf],
+    loader: &L,
+    f: F,
+    cancelled: &AtomicBool,
+    progress: &P,
+) -> Vec<Profile>
+where
+    L: ProfileLoader + ?Sized,
+    F: Fn(&Profile) -> bool + Send + Sync,
+    P: Fn(usize, usize) + Send + Sync + ?Sized,
+{
+    use rayon::prelude::*;
+    let total = file_paths.len();
+    // Holding the lock while calling `progress` keeps reported counts increasing.
+    let processed = Mutex::new(0);
     file_paths
         .par_iter()
-        .map(|path| Profile::from_file(path))
-        .filter_map(Result::ok)
-        .filter(f)
+        .filter(|_| !cancelled.load(Ordering::Relaxed))
+        .filter_map(|path| {
+            let profile = loader.load(path).ok();
+            let mut processed = processed.lock().unwrap_or_else(|e| e.into_inner());
+            *processed += 1;
+            progress(*processed, total);
+            profile
+        })
+        .filter(|profile| f(profile))
         .collect()
 }
 
+/// Filters files using predicate function `f` until the `token` is set.
+///
+/// Files that aren't read by the time the `token` is `true` (e.g. it's set by
+/// a Ctrl-C handler) are skipped, so only profiles read so far are returned.
+pub fn filter_cancellable<F>(file_paths: Vec<PathBuf>, f: F, token: Arc<AtomicBool>) -> Vec<Profile>
+where
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    filter_collected_until(&file_paths, &FileSystemLoader, f, &token, &|_, _| {})
+}
+
+/// Runs `op` in a thread pool with `concurrency` threads, `0` means to use all
+/// available cores.
+fn install<R, OP>(concurrency: usize, op: OP) -> R
+where
+    R: Send,
+    OP: FnOnce() -> R + Send,
+{
+    match rayon::ThreadPoolBuilder::new()
+        .num_threads(concurrency)
+        .build()
+    {
+        Ok(pool) => pool.install(op),
+        Err(_) => op(),
+    }
+}
+
 /// Filters files of a directory using predicate function `f`.
 ///
-/// Conveniently combines [`file_paths`] and [`filter`] functions together.
+/// Conveniently combines [`file_paths_sorted`] and [`filter`] functions together,
+/// so profiles are in the order of their file paths.
 pub fn filter_dir<F>(dir: &Path, f: F) -> Result<Vec<Profile>>
 where
     F: Fn(&Profile) -> bool + Send + Sync,
 {
-    Ok(filter(file_paths(dir)?.collect(), f))
+    Ok(filter_collected(
+        &file_paths_sorted(dir)?,
+        &FileSystemLoader,
+        f,
+    ))
+}
+
+/// Filters files of a directory using predicate function `f` calling `progress`
+/// with numbers of processed and all files after each file is read.
+///
+/// Files are read in parallel, so `progress` may be called from different
+/// threads, but the number of processed files only increases.
+pub fn filter_dir_with_progress<F, P>(dir: &Path, f: F, progress: P) -> Result<Vec<Profile>>
+where
+    F: Fn(&Profile) -> bool + Send + Sync,
+    P: Fn(usize, usize) + Send + Sync,
+{
+    Ok(filter_collected_until(
+        &file_paths_sorted(dir)?,
+        &FileSystemLoader,
+        f,
+        &AtomicBool::new(false),
+        &progress,
+    ))
+}
+
+/// Filters profiles of a directory loaded by `loader` using predicate function `f`.
+///
+/// Conveniently combines [`file_paths_sorted`] and [`filter_with_loader`] functions
+/// together.
+pub fn filter_dir_with_loader<L, F>(dir: &Path, loader: &L, f: F) -> Result<Vec<Profile>>
+where
+    L: ProfileLoader + ?Sized,
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    Ok(filter_collected(&file_paths_sorted(dir)?, loader, f))
+}
+
+/// Filters files of several directories using predicate function `f`.
+///
+/// Profiles are in the order of `dirs` and then of their file paths, a profile
+/// with the same uuid as a profile of a preceding directory is skipped.
+pub fn filter_dirs<F>(dirs: &[&Path], f: F) -> Result<Vec<Profile>>
+where
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    filter_dirs_with_options(dirs, f, &ScanOptions::default())
+}
+
+/// Options of [`filter_dirs_with_options`].
+pub struct ScanOptions<'a> {
+    /// Loads profiles, [`FileSystemLoader`] by default.
+    pub loader: &'a dyn ProfileLoader,
+    /// The maximum number of files read at the same time, `0` means to use all
+    /// available cores.
+    pub concurrency: usize,
+    /// Files that aren't read by the time the token is `true` (e.g. it's set by
+    /// a Ctrl-C handler) are skipped, so only profiles read so far are returned.
+    pub token: Option<Arc<AtomicBool>>,
+    /// Called with numbers of processed and all files after each file is read,
+    /// see [`filter_dir_with_progress`].
+    pub progress: Option<&'a (dyn Fn(usize, usize) + Send + Sync)>,
+    /// Reads `*.provisionprofile` files as well, see [`file_paths_all`].
+    pub include_mac: bool,
+}
+
+impl Default for ScanOptions<'_> {
+    fn default() -> Self {
+        Self {
+            loader: &FileSystemLoader,
+            concurrency: 0,
+            token: None,
+            progress: None,
+            include_mac: false,
+        }
+    }
+}
+
+/// Filters files of several directories using predicate function `f` the way
+/// `options` specify.
+///
+/// The same as [`filter_dirs`] otherwise.
+pub fn filter_dirs_with_options<F>(
+    dirs: &[&Path],
+    f: F,
+    options: &ScanOptions,
+) -> Result<Vec<Profile>>
+where
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    let file_paths = file_paths_of_dirs(dirs, options.include_mac)?;
+    let not_cancelled = AtomicBool::new(false);
+    let cancelled = options.token.as_deref().unwrap_or(&not_cancelled);
+    let progress = options.progress.unwrap_or(&|_, _| {});
+    Ok(dedup_by_uuid(install(options.concurrency, || {
+        filter_collected_until(&file_paths, options.loader, f, cancelled, progress)
+    })))
+}
+
+fn file_paths_of_dirs(dirs: &[&Path], include_mac: bool) -> Result<Vec<PathBuf>> {
+    let is_profile = if include_mac {
+        is_any_provision_profile
+    } else {
+        is_mobileprovision
+    };
+    let mut file_paths = Vec::new();
+    for dir in dirs {
+        let len = file_paths.len();
+        file_paths.extend(file_paths_matching(dir, is_profile)?);
+        file_paths[len..].sort();
+    }
+    Ok(file_paths)
+}
+
+fn dedup_by_uuid(mut profiles: Vec<Profile>) -> Vec<Profile> {
+    let mut uuids = HashSet::new();
+    profiles.retain(|profile| uuids.insert(profile.info.uuid.clone()));
+    profiles
+}
+
+/// Filters files of a directory using predicate function `f` reading at most
+/// `concurrency` files at the same time.
+///
+/// Conveniently combines [`file_paths_sorted`] and [`filter_with_concurrency`]
+/// functions together.
+pub fn filter_dir_with_concurrency<F>(dir: &Path, concurrency: usize, f: F) -> Result<Vec<Profile>>
+where
+    F: Fn(&Profile) -> bool + Send + Sync,
+{
+    let file_paths = file_paths_sorted(dir)?;
+    Ok(install(concurrency, || {
+        filter_collected(&file_paths, &FileSystemLoader, f)
+    }))
+}
+
+/// Returns a profile with `uuid` from a directory.
+///
+/// The `concurrency` has the same meaning as for [`filter_with_concurrency`].
+///
+/// # Errors
+/// This function will return [`Error::NotFound`] if there is no such profile.
+pub fn find_by_uuid(dir: &Path, uuid: &str, concurrency: usize) -> Result<Profile> {
+    filter_dir_with_concurrency(dir, concurrency, |profile| profile.info.uuid == uuid)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| Error::NotFound(uuid.to_owned()))
+}
+
+/// Returns profiles from a directory that have any of `ids` paired with the
+/// matched id.
+///
+/// Every id is paired with `None` if there is no profile with it. An id can
+/// match several profiles (e.g. by name), so it's paired with each of them.
+/// See [`Info::has_ids`] for details. The `concurrency` has the same meaning
+/// as for [`filter_with_concurrency`].
+///
+/// [`Info::has_ids`]: crate::profile::Info::has_ids
+pub fn find_by_ids(
+    dir: &Path,
+    ids: &[String],
+    concurrency: usize,
+) -> Result<Vec<(String, Option<Profile>)>> {
+    let profiles =
+        filter_dir_with_concurrency(dir, concurrency, |profile| profile.info.has_ids(ids))?;
+    let mut result = Vec::new();
+    for id in ids {
+        let len = result.len();
+        for profile in profiles.iter().filter(|profile| profile.info.has_ids([id])) {
+            result.push((id.clone(), Some(profile.clone())));
+        }
+        if result.len() == len {
+            result.push((id.clone(), None));
+        }
+    }
+    Ok(result)
+}
+
+/// Groups profiles by the date of their creation at the `offset`, e.g.
+/// `UtcOffset::UTC` for the same dates as [`Info::creation_day`].
+///
+/// Profiles of a group are in the order of `profiles`.
+///
+/// [`Info::creation_day`]: crate::profile::Info::creation_day
+pub fn group_by_creation_date(
+    profiles: Vec<Profile>,
+    offset: time::UtcOffset,
+) -> BTreeMap<time::Date, Vec<Profile>> {
+    let mut groups = BTreeMap::<_, Vec<_>>::new();
+    for profile in profiles {
+        let date = time::OffsetDateTime::from(profile.info.creation_date)
+            .to_offset(offset)
+            .date();
+        groups.entry(date).or_default().push(profile);
+    }
+    groups
 }
 
 /// Returns internals of a provisioning profile.
+///
+/// The returned xml can be parsed again using [`Info::from_clean_xml`] without
+/// searching for a plist content.
+///
+/// [`Info::from_clean_xml`]: crate::profile::Info::from_clean_xml
 pub fn show(file_path: &Path) -> Result<String> {
     let mut buf = Vec::new();
     File::open(file_path)
         .and_then(|mut file| file.read_to_end(&mut buf))
-        .map_err(|err| err.into())
+        .map_err(Error::with_path(file_path))
         .and_then(|_| {
             plist_extractor::find(&buf)
                 .ok_or_else(|| Error::Own(format!("Couldn't parse '{}'", file_path.display())))
@@ -112,6 +624,110 @@ pub fn show(file_path: &Path) -> Result<String> {
         .and_then(|data| String::from_utf8(data.to_owned()).map_err(|err| err.into()))
 }
 
+/// Writes the raw content of a provisioning profile, i.e. the signed envelope,
+/// into the `writer`.
+///
+/// # Errors
+/// This function will return an error if the file doesn't have an extension of
+/// a provisioning profile, see [`is_any_provision_profile`].
+pub fn show_raw<W: Write + ?Sized>(file_path: &Path, writer: &mut W) -> Result<()> {
+    if !is_any_provision_profile(file_path) {
+        return Err(Error::Own(format!(
+            "'{}' is not a provisioning profile",
+            file_path.display()
+        )));
+    }
+    let mut file = File::open(file_path).map_err(Error::with_path(file_path))?;
+    io::copy(&mut file, writer)?;
+    Ok(())
+}
+
+/// Returns internals of a provisioning profile with indented xml.
+///
+/// See [`show`] and [`pretty_print`] for details.
+pub fn show_pretty(file_path: &Path) -> Result<String> {
+    pretty_print(&show(file_path)?)
+}
+
+/// Re-formats `xml` indenting nested elements with tabs.
+///
+/// Whitespace between elements is replaced, text values are kept as is.
+pub fn pretty_print(xml: &str) -> Result<String> {
+    use quick_xml::events::{BytesText, Event};
+
+    let mut reader = quick_xml::Reader::from_str(xml);
+    let mut writer = quick_xml::Writer::new_with_indent(Vec::new(), b'\t', 1);
+    // Whitespace or nothing between a start and an end tag is a value of a
+    // leaf element, so it's written as is.
+    let mut leaf_value = None;
+    loop {
+        match reader.read_event()? {
+            Event::Eof => break,
+            Event::Text(text) if text.iter().all(u8::is_ascii_whitespace) => {
+                leaf_value = leaf_value.take().map(|_| text);
+            }
+            event => {
+                if let (Event::End(_), Some(text)) = (&event, leaf_value.take()) {
+                    writer.write_event(Event::Text(text))?;
+                }
+                if matches!(event, Event::Start(_)) {
+                    leaf_value = Some(BytesText::new(""));
+                }
+                writer.write_event(event)?;
+            }
+        }
+    }
+    Ok(String::from_utf8(writer.into_inner())?)
+}
+
+/// Removes a provisioning profile.
+///
+/// The file is moved to the trash unless `permanently` is `true`.
+pub fn remove(file_path: &Path, permanently: bool) -> Result<()> {
+    if permanently {
+        fs::remove_file(file_path).map_err(Error::with_path(file_path))?;
+    } else {
+        trash::delete(file_path)?;
+    }
+    Ok(())
+}
+
+/// Returns `data` encoded as base64 with the standard alphabet wrapped into lines
+/// of 76 characters, e.g. to store a profile in an environment variable.
+pub fn encode_base64(data: &[u8]) -> String {
+    use base64::prelude::{Engine, BASE64_STANDARD};
+    BASE64_STANDARD
+        .encode(data)
+        .as_bytes()
+        .chunks(76)
+        .map(String::from_utf8_lossy)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Returns data decoded from base64 with the standard alphabet, whitespace
+/// including line breaks is ignored.
+///
+/// # Errors
+/// This function will return an error if `s` isn't valid base64.
+pub fn decode_base64(s: &str) -> Result<Vec<u8>> {
+    use base64::prelude::{Engine, BASE64_STANDARD};
+    let s = s
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .collect::<String>();
+    BASE64_STANDARD
+        .decode(s)
+        .map_err(|err| Error::Own(format!("Invalid base64: {}", err)))
+}
+
+/// Returns internals of a provisioning profile from its `data`.
+pub fn show_data(data: &[u8]) -> Result<String> {
+    let xml = plist_extractor::find(data)
+        .ok_or_else(|| Error::Own("Couldn't find plist content.".to_owned()))?;
+    Ok(String::from_utf8(xml.to_owned())?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -121,13 +737,441 @@ mod tests {
         use std::fs::File;
 
         let temp_dir = tempfile::tempdir().unwrap();
-        let result = file_paths(temp_dir.path()).map(|iter| iter.count()).unwrap();
+        let result = file_paths(temp_dir.path())
+            .map(|iter| iter.count())
+            .unwrap();
         assert_eq!(result, 0);
 
         File::create(temp_dir.path().join("1.mobileprovision")).unwrap();
         File::create(temp_dir.path().join("2.mobileprovision")).unwrap();
         File::create(temp_dir.path().join("3.txt")).unwrap();
-        let result = file_paths(temp_dir.path()).map(|iter| iter.count()).unwrap();
+        let result = file_paths(temp_dir.path())
+            .map(|iter| iter.count())
+            .unwrap();
+        assert_eq!(result, 2);
+    }
+
+    #[test]
+    fn filter_all_provisioning_profile_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        for name in ["1.mobileprovision", "2.provisionprofile", "3.txt"] {
+            fs::File::create(temp_dir.path().join(name)).unwrap();
+        }
+        assert_eq!(file_paths(temp_dir.path()).unwrap().count(), 1);
+        assert_eq!(file_paths_all(temp_dir.path()).unwrap().count(), 2);
+        assert!(is_provisionprofile(Path::new("1.PROVISIONPROFILE")));
+        assert!(!is_provisionprofile(Path::new("1.mobileprovision")));
+        assert!(is_any_provision_profile(Path::new("1.mobileprovision")));
+        assert!(!is_any_provision_profile(Path::new("1.txt")));
+    }
+
+    #[test]
+    fn sorted_mobileprovision_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        for name in [
+            "b.mobileprovision",
+            "c.mobileprovision",
+            "a.mobileprovision",
+        ] {
+            fs::File::create(temp_dir.path().join(name)).unwrap();
+        }
+        assert_eq!(
+            file_paths_sorted(temp_dir.path()).unwrap(),
+            vec![
+                temp_dir.path().join("a.mobileprovision"),
+                temp_dir.path().join("b.mobileprovision"),
+                temp_dir.path().join("c.mobileprovision"),
+            ]
+        );
+    }
+
+    #[test]
+    fn mobileprovision_extension_ignoring_case() {
+        assert!(is_mobileprovision(Path::new("1.mobileprovision")));
+        assert!(is_mobileprovision(Path::new("1.MOBILEPROVISION")));
+        assert!(is_mobileprovision(Path::new("1.MobileProvision")));
+        assert!(!is_mobileprovision(Path::new("1.mobileprovision.txt")));
+        assert!(!is_mobileprovision(Path::new("mobileprovision")));
+    }
+
+    #[test]
+    fn filter_uppercase_mobileprovision_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        fs::File::create(temp_dir.path().join("1.MOBILEPROVISION")).unwrap();
+        let result = file_paths(temp_dir.path())
+            .map(|iter| iter.count())
+            .unwrap();
+        assert_eq!(result, 1);
+    }
+
+    struct MockLoader;
+
+    impl ProfileLoader for MockLoader {
+        fn load(&self, path: &Path) -> Result<Profile> {
+            let uuid = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .ok_or_else(|| Error::Own("Invalid path".to_owned()))?;
+            let data = include_bytes!("../tests/test.xml");
+            let mut info = profile::Info::from_xml_data(data).unwrap();
+            info.uuid = uuid.to_owned();
+            Ok(Profile {
+                path: path.to_owned(),
+                info,
+            })
+        }
+    }
+
+    #[test]
+    fn filter_using_loader() {
+        let paths = vec!["a.mobileprovision".into(), "b.mobileprovision".into()];
+        let profiles = filter_with_loader(paths, &MockLoader, |profile| profile.info.uuid == "b");
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].path, Path::new("b.mobileprovision"));
+    }
+
+    #[test]
+    fn filter_using_loader_preserves_order() {
+        let paths = (0..100)
+            .map(|i| PathBuf::from(format!("{}.mobileprovision", i)))
+            .collect::<Vec<_>>();
+        let profiles = filter_with_loader(paths.clone(), &MockLoader, |_| true);
+        let profile_paths = profiles
+            .into_iter()
+            .map(|profile| profile.path)
+            .collect::<Vec<_>>();
+        assert_eq!(profile_paths, paths);
+    }
+
+    #[test]
+    fn filter_file_paths_without_collecting() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data = include_bytes!("../tests/test.xml");
+        fs::write(temp_dir.path().join("1.mobileprovision"), data).unwrap();
+        fs::write(temp_dir.path().join("2.mobileprovision"), data).unwrap();
+        let profiles = filter(file_paths(temp_dir.path()).unwrap(), |_| true);
+        assert_eq!(profiles.len(), 2);
+    }
+
+    #[test]
+    fn filter_with_limited_concurrency() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data = include_bytes!("../tests/test.xml");
+        fs::write(temp_dir.path().join("1.mobileprovision"), data).unwrap();
+        fs::write(temp_dir.path().join("2.mobileprovision"), data).unwrap();
+        let profiles = filter_dir_with_concurrency(temp_dir.path(), 1, |_| true).unwrap();
+        assert_eq!(profiles.len(), 2);
+    }
+
+    #[test]
+    fn filter_with_progress() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data = include_bytes!("../tests/test.xml");
+        fs::write(temp_dir.path().join("1.mobileprovision"), data).unwrap();
+        fs::write(temp_dir.path().join("2.mobileprovision"), b"corrupt").unwrap();
+        fs::write(temp_dir.path().join("3.mobileprovision"), data).unwrap();
+        let reports = Mutex::new(Vec::new());
+        let profiles = filter_dir_with_progress(
+            temp_dir.path(),
+            |_| true,
+            |processed, total| reports.lock().unwrap().push((processed, total)),
+        )
+        .unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(reports.into_inner().unwrap(), vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn filter_until_cancelled() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data = include_bytes!("../tests/test.xml");
+        fs::write(temp_dir.path().join("1.mobileprovision"), data).unwrap();
+        let file_paths = file_paths_sorted(temp_dir.path()).unwrap();
+        let token = Arc::new(AtomicBool::new(false));
+        assert_eq!(
+            filter_cancellable(file_paths.clone(), |_| true, token.clone()).len(),
+            1
+        );
+        token.store(true, Ordering::Relaxed);
+        assert!(filter_cancellable(file_paths, |_| true, token.clone()).is_empty());
+        let options = ScanOptions {
+            concurrency: 1,
+            token: Some(token),
+            ..Default::default()
+        };
+        assert!(
+            filter_dirs_with_options(&[temp_dir.path()], |_| true, &options)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn filter_dirs_using_options() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        for name in ["b.provisionprofile", "a.mobileprovision", "c.txt"] {
+            fs::File::create(temp_dir.path().join(name)).unwrap();
+        }
+        let dirs = [temp_dir.path()];
+        let paths = |include_mac| {
+            let options = ScanOptions {
+                loader: &MockLoader,
+                include_mac,
+                ..Default::default()
+            };
+            filter_dirs_with_options(&dirs, |_| true, &options)
+                .unwrap()
+                .into_iter()
+                .map(|profile| profile.path)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            paths(false),
+            vec![temp_dir.path().join("a.mobileprovision")]
+        );
+        assert_eq!(
+            paths(true),
+            vec![
+                temp_dir.path().join("a.mobileprovision"),
+                temp_dir.path().join("b.provisionprofile"),
+            ]
+        );
+    }
+
+    #[test]
+    fn filter_several_dirs_without_duplicates() {
+        let temp_dirs = [tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap()];
+        let data = include_bytes!("../tests/test.xml");
+        let other = String::from_utf8_lossy(data).replace(
+            "fbcdef0a-af78-4a11-b1f1-87cb897bfa8e",
+            "00000000-0000-0000-0000-000000000000",
+        );
+        fs::write(temp_dirs[0].path().join("1.mobileprovision"), data).unwrap();
+        fs::write(temp_dirs[1].path().join("1.mobileprovision"), data).unwrap();
+        fs::write(temp_dirs[1].path().join("2.mobileprovision"), other).unwrap();
+        let dirs = [temp_dirs[0].path(), temp_dirs[1].path()];
+        let profiles = filter_dirs(&dirs, |_| true).unwrap();
+        let paths = profiles
+            .iter()
+            .map(|profile| profile.path.clone())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            vec![
+                temp_dirs[0].path().join("1.mobileprovision"),
+                temp_dirs[1].path().join("2.mobileprovision"),
+            ]
+        );
+        let options = ScanOptions {
+            concurrency: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            filter_dirs_with_options(&dirs, |_| true, &options).unwrap(),
+            profiles
+        );
+    }
+
+    #[test]
+    fn default_directory() {
+        let home = Some(PathBuf::from("/nonexistent"));
+        let xdg_data_home = Some(OsString::from("/nonexistent/data"));
+        if cfg!(target_os = "macos") {
+            let expected = Path::new("/nonexistent/Library/MobileDevice/Provisioning Profiles");
+            assert_eq!(directory_in(home.clone(), None).unwrap(), expected);
+            assert_eq!(directory_in(home, xdg_data_home).unwrap(), expected);
+        } else {
+            assert_eq!(
+                directory_in(home.clone(), None).unwrap(),
+                Path::new("/nonexistent/.local/share/mprovision/profiles")
+            );
+            assert_eq!(
+                directory_in(home.clone(), Some(OsString::new())).unwrap(),
+                Path::new("/nonexistent/.local/share/mprovision/profiles")
+            );
+            assert_eq!(
+                directory_in(None, xdg_data_home).unwrap(),
+                Path::new("/nonexistent/data/mprovision/profiles")
+            );
+        }
+        assert!(directory_in(None, None).is_err());
+        assert_eq!(dir_or_default(Some(".".into())).unwrap(), Path::new("."));
+    }
+
+    #[test]
+    fn find_profiles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data = include_bytes!("../tests/test.xml");
+        fs::write(temp_dir.path().join("1.mobileprovision"), data).unwrap();
+        let uuid = "fbcdef0a-af78-4a11-b1f1-87cb897bfa8e";
+        assert_eq!(
+            find_by_uuid(temp_dir.path(), uuid, 0).unwrap().info.uuid,
+            uuid
+        );
+        let ids = ["com.testapp".to_owned(), "a".to_owned()];
+        let found = find_by_ids(temp_dir.path(), &ids, 0).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].0, "com.testapp");
+        assert_eq!(found[0].1.as_ref().unwrap().info.uuid, uuid);
+        assert_eq!(found[1].0, "a");
+        assert!(found[1].1.is_none());
+    }
+
+    #[test]
+    fn pretty_print_xml() {
+        let xml = "<?xml version=\"1.0\"?><plist><dict><key>A</key>\
+                   <array> <string> a </string></array></dict></plist>";
+        assert_eq!(
+            pretty_print(xml).unwrap(),
+            "<?xml version=\"1.0\"?>\n<plist>\n\t<dict>\n\t\t<key>A</key>\n\
+             \t\t<array>\n\t\t\t<string> a </string>\n\t\t</array>\n\t</dict>\n</plist>"
+        );
+    }
+
+    #[test]
+    fn pretty_print_keeps_whitespace_values() {
+        let xml = "<plist><array> <string> </string>\n<string></string></array></plist>";
+        assert_eq!(
+            pretty_print(xml).unwrap(),
+            "<plist>\n\t<array>\n\t\t<string> </string>\n\t\t<string></string>\n\t</array>\n</plist>"
+        );
+    }
+
+    #[test]
+    fn show_pretty_profile() {
+        let xml = show_pretty(Path::new("tests/test.xml")).unwrap();
+        assert!(xml.contains("\n\t\t<key>AppIDName</key>\n\t\t<string>TestApp</string>\n"));
+        assert_eq!(
+            profile::Info::from_clean_xml(xml.as_bytes()).unwrap(),
+            profile::Info::from_clean_xml(show(Path::new("tests/test.xml")).unwrap().as_bytes())
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn show_raw_profile() {
+        let path = Path::new("tests/signed.mobileprovision");
+        let mut output = Vec::new();
+        show_raw(path, &mut output).unwrap();
+        assert_eq!(output, fs::read(path).unwrap());
+        assert!(show_raw(Path::new("tests/test.xml"), &mut output).is_err());
+    }
+
+    #[test]
+    fn group_profiles_by_creation_date() {
+        let profile = |uuid: &str, hours: u64| Profile {
+            path: PathBuf::from(uuid),
+            info: profile::Info {
+                uuid: uuid.into(),
+                creation_date: std::time::SystemTime::UNIX_EPOCH
+                    + std::time::Duration::from_secs(hours * 60 * 60),
+                ..profile::Info::empty()
+            },
+        };
+        let groups = |offset| {
+            let profiles = vec![profile("b", 24), profile("a", 0), profile("c", 47)];
+            group_by_creation_date(profiles, offset)
+                .iter()
+                .map(|(date, profiles)| {
+                    let uuids = profiles
+                        .iter()
+                        .map(|p| p.info.uuid.clone())
+                        .collect::<Vec<_>>();
+                    (date.to_string(), uuids)
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            groups(time::UtcOffset::UTC),
+            vec![
+                ("1970-01-01".to_owned(), vec!["a".to_owned()]),
+                (
+                    "1970-01-02".to_owned(),
+                    vec!["b".to_owned(), "c".to_owned()]
+                ),
+            ]
+        );
+        assert_eq!(
+            groups(time::UtcOffset::from_hms(-1, 0, 0).unwrap()),
+            vec![
+                ("1969-12-31".to_owned(), vec!["a".to_owned()]),
+                ("1970-01-01".to_owned(), vec!["b".to_owned()]),
+                ("1970-01-02".to_owned(), vec!["c".to_owned()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_and_decode_base64() {
+        let data = fs::read("tests/signed.mobileprovision").unwrap();
+        let encoded = encode_base64(&data);
+        assert!(encoded.lines().all(|line| line.len() <= 76));
+        assert_eq!(encoded.lines().next().map(str::len), Some(76));
+        assert_eq!(decode_base64(&encoded).unwrap(), data);
+        assert_eq!(encode_base64(b"abc"), "YWJj");
+        assert!(decode_base64("a!").is_err());
+    }
+
+    #[test]
+    fn find_missing_profiles() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let err = find_by_uuid(temp_dir.path(), "123", 0).unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+        assert_eq!(err.to_string(), "Profile not found: 123");
+        let found = find_by_ids(temp_dir.path(), &["a".to_owned()], 0).unwrap();
+        assert_eq!(found.len(), 1);
+        assert!(found[0].1.is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn filter_readable_mobileprovision_files() {
+        use std::fs::File;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        File::create(temp_dir.path().join("1.mobileprovision")).unwrap();
+        std::os::unix::fs::symlink(
+            temp_dir.path().join("missing"),
+            temp_dir.path().join("2.mobileprovision"),
+        )
+        .unwrap();
+        let result = file_paths(temp_dir.path())
+            .map(|iter| iter.count())
+            .unwrap();
+        assert_eq!(result, 2);
+        let result = file_paths_readable(temp_dir.path())
+            .map(|iter| iter.count())
+            .unwrap();
+        assert_eq!(result, 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn filter_mobileprovision_symlinks() {
+        use std::fs::File;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let target_dir = tempfile::tempdir().unwrap();
+        let target = target_dir.path().join("1.mobileprovision");
+        File::create(&target).unwrap();
+        std::os::unix::fs::symlink(&target, temp_dir.path().join("profile")).unwrap();
+        std::os::unix::fs::symlink(target_dir.path(), temp_dir.path().join("2.mobileprovision"))
+            .unwrap();
+        File::create(temp_dir.path().join("3.mobileprovision")).unwrap();
+        let result = file_paths(temp_dir.path())
+            .map(|iter| iter.count())
+            .unwrap();
         assert_eq!(result, 2);
+        let mut result = file_paths_follow_symlinks(temp_dir.path())
+            .unwrap()
+            .collect::<Vec<_>>();
+        result.sort();
+        assert_eq!(
+            result,
+            vec![
+                temp_dir.path().join("3.mobileprovision"),
+                temp_dir.path().join("profile"),
+            ]
+        );
     }
 }
@@ -1,4 +1,5 @@
 use memchr::memmem;
+use std::borrow::Cow;
 
 const PLIST_PREFIX: &[u8] = b"<?xml version=";
 const PLIST_SUFFIX: &[u8] = b"</plist>";
@@ -7,6 +8,20 @@ const PLIST_SUFFIX: &[u8] = b"</plist>";
 ///
 /// Since mobileprovision files contain "garbage" at the start and the end you need to extract
 /// a plist content before the xml parsing.
+///
+/// This function is also re-exported as [`crate::find_plist_in_data`] for use with arbitrary
+/// binary data containing an embedded mobileprovision content.
+///
+/// # Examples
+///
+/// ```
+/// let data = b"garbage<?xml version=\"1.0\"?><plist></plist>garbage";
+/// assert_eq!(
+///     mprovision::find_plist_in_data(data),
+///     Some(&b"<?xml version=\"1.0\"?><plist></plist>"[..])
+/// );
+/// assert_eq!(mprovision::find_plist_in_data(b"garbage"), None);
+/// ```
 pub fn find(data: &[u8]) -> Option<&[u8]> {
     let start_i = memmem::find(data, PLIST_PREFIX);
     let end_i = memmem::rfind(data, PLIST_SUFFIX).map(|i| i + PLIST_SUFFIX.len());
@@ -20,10 +35,59 @@ pub fn find(data: &[u8]) -> Option<&[u8]> {
     None
 }
 
+/// Finds all non-overlapping plist contents in a `data` scanning it left to right.
+///
+/// Unlike [`find`] each plist content ends at the first suffix after its prefix.
+pub fn find_all(data: &[u8]) -> Vec<&[u8]> {
+    let mut result = Vec::new();
+    let mut offset = 0;
+    while let Some(start_i) = memmem::find(&data[offset..], PLIST_PREFIX).map(|i| offset + i) {
+        let Some(end_i) =
+            memmem::find(&data[start_i..], PLIST_SUFFIX).map(|i| start_i + i + PLIST_SUFFIX.len())
+        else {
+            break;
+        };
+        result.push(&data[start_i..end_i]);
+        offset = end_i;
+    }
+    result
+}
+
+/// Replaces `\r\n` line endings with `\n` in a `data`.
+///
+/// The `data` is borrowed as is if it doesn't contain `\r`.
+pub fn normalize_line_endings(data: &[u8]) -> Cow<'_, [u8]> {
+    if memchr::memchr(b'\r', data).is_none() {
+        return Cow::Borrowed(data);
+    }
+    let mut normalized = Vec::with_capacity(data.len());
+    let mut rest = data;
+    while let Some(i) = memmem::find(rest, b"\r\n") {
+        normalized.extend_from_slice(&rest[..i]);
+        normalized.push(b'\n');
+        rest = &rest[(i + 2)..];
+    }
+    normalized.extend_from_slice(rest);
+    Cow::Owned(normalized)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_normalize_line_endings() {
+        let data: &[u8] = b"<a>\r\n<b>x\ry</b>\r\n</a>\r\n";
+        assert_eq!(
+            normalize_line_endings(data),
+            Cow::<[u8]>::Owned(b"<a>\n<b>x\ry</b>\n</a>\n".to_vec())
+        );
+        assert!(matches!(
+            normalize_line_endings(b"<a>\n</a>"),
+            Cow::Borrowed(b"<a>\n</a>")
+        ));
+    }
+
     #[test]
     fn test_find_plist() {
         let data: &[u8] = b"<?xml version=</plist>";
@@ -34,4 +98,19 @@ mod tests {
         let data: &[u8] = b"   <?xml version=abcd</plist>   ";
         assert_eq!(find(data), Some(b"<?xml version=abcd</plist>" as &[u8]));
     }
+    #[test]
+    fn test_find_all_plists() {
+        let data: &[u8] = b"  <?xml version=a</plist> \n <?xml version=b</plist> <?xml version=";
+        assert_eq!(
+            find_all(data),
+            vec![
+                b"<?xml version=a</plist>" as &[u8],
+                b"<?xml version=b</plist>" as &[u8]
+            ]
+        );
+    }
+    #[test]
+    fn test_find_all_without_plists() {
+        assert!(find_all(b"</plist><?xml version=").is_empty());
+    }
 }
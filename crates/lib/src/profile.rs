@@ -1,39 +1,430 @@
+#[cfg(feature = "strict-validation")]
+use crate::validation::ValidationError;
 use crate::{Error, Result};
 use serde::Deserialize;
+use std::fmt;
 use std::fs::File;
 use std::io::{self, Read};
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use time::format_description::FormatItem;
+use time::macros::format_description;
+use time::OffsetDateTime;
 
 /// Represents a file with a provisioning profile info.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Profile {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_path"))]
     pub path: PathBuf,
     pub info: Info,
 }
 
+#[cfg(feature = "serde")]
+fn serialize_path<S: serde::Serializer>(
+    path: &Path,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+    serializer.collect_str(&path.display())
+}
+
 impl Profile {
     /// Returns instance of the `Profile` parsed from a file.
     pub fn from_file(path: &Path) -> Result<Self> {
-        let mut buf = Vec::new();
-        File::open(path)?.read_to_end(&mut buf)?;
-        let info =
-            Info::from_xml_data(&buf).ok_or_else(|| Error::Own("Couldn't parse file.".into()))?;
+        Info::from_file(path).map(|info| Self {
+            path: path.to_owned(),
+            info,
+        })
+    }
+
+    /// Returns `true` if the profile is signed by Apple and wasn't tampered.
+    ///
+    /// See [`crate::signature`] for details.
+    pub fn verify_signature(&self) -> Result<bool> {
+        crate::signature::verify(&std::fs::read(&self.path).map_err(Error::with_path(&self.path))?)
+    }
+}
+
+/// Represents a file with a provisioning profile info and the raw content of the file.
+///
+/// Unlike [`Profile`] the content is kept in memory, so it can be used again
+/// without reading the file, e.g. to verify the signature. Clones share the content.
+#[derive(Debug, Clone)]
+pub struct ProfileRef {
+    pub path: PathBuf,
+    pub info: Info,
+    data: Arc<Vec<u8>>,
+}
+
+impl ProfileRef {
+    /// Returns instance of the `ProfileRef` parsed from a file.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let buf = read_file(path)?;
+        let info = Info::parse(path, &buf)?;
         Ok(Self {
             path: path.to_owned(),
             info,
+            data: Arc::new(buf),
         })
     }
+
+    /// Returns the raw content of the file.
+    pub fn raw_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Returns `true` if the profile is signed by Apple and wasn't tampered.
+    ///
+    /// The same as [`Profile::verify_signature`] but doesn't read the file.
+    pub fn verify_signature(&self) -> Result<bool> {
+        crate::signature::verify(&self.data)
+    }
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut buf))
+        .map_err(Error::with_path(path))?;
+    Ok(buf)
+}
+
+impl From<ProfileRef> for Profile {
+    fn from(profile: ProfileRef) -> Self {
+        Self {
+            path: profile.path,
+            info: profile.info,
+        }
+    }
+}
+
+/// Profiles are ordered by expiration dates, so sorted profiles start with the
+/// soonest-expiring ones. Profiles with the same expiration date are ordered by
+/// uuids, and profiles are equal if both their expiration dates and uuids are.
+impl Ord for Profile {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.info.expiration_date, &self.info.uuid)
+            .cmp(&(other.info.expiration_date, &other.info.uuid))
+    }
+}
+
+impl PartialOrd for Profile {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Profile {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
 }
 
+impl Eq for Profile {}
+
 /// Represents provisioning profile info.
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Info {
     pub uuid: String,
     pub name: String,
-    pub app_identifier: String,
+    /// A human-readable name of the app id, it's empty if the profile doesn't have one.
+    pub app_id_name: String,
+    pub app_identifier: AppIdentifier,
+    /// A name of the development team, it's empty if the profile doesn't have one.
+    pub team_name: String,
+    /// An identifier of the development team, it's the prefix of the `app_identifier`
+    /// if the profile doesn't have one.
+    pub team_identifier: TeamId,
     pub creation_date: SystemTime,
     pub expiration_date: SystemTime,
+    /// DER encoded developer certificates embedded in the profile.
+    pub developer_certificates: Vec<Vec<u8>>,
+    /// Sorted UDIDs of devices the profile is provisioned for without duplicates.
+    pub provisioned_devices: Vec<String>,
+    /// Whether the profile is provisioned for all devices (enterprise profiles).
+    pub provisions_all_devices: bool,
+    /// Whether a debugger can attach to the app, the `get-task-allow` entitlement.
+    pub get_task_allow: bool,
+    /// Platforms of the profile as they're named in the profile, e.g. `iOS` or
+    /// `OSX`, it's empty if the profile doesn't specify them.
+    pub platforms: Vec<String>,
+    /// Intended validity duration of the profile in days, `0` if it's unknown.
+    pub time_to_live: u32,
+    /// A version of the profile format, `1` if the profile doesn't specify it.
+    pub version: u32,
+}
+
+/// Represents a subset of provisioning profile info that is enough to list profiles.
+///
+/// It's cheaper to parse than the `Info`, see [`Info::from_xml_data_minimal`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct MinimalInfo {
+    pub uuid: String,
+    pub name: String,
+    pub app_identifier: AppIdentifier,
+    pub expiration_date: SystemTime,
+}
+
+/// Represents an application identifier of a provisioning profile, i.e.
+/// `TEAM.bundle.id` string.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct AppIdentifier(pub String);
+
+/// Represents an identifier of a development team.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct TeamId(pub String);
+
+/// Represents a bundle identifier of an app, it may contain a wildcard.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct BundleId(pub String);
+
+impl AppIdentifier {
+    /// Returns the application identifier as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the team id, the part before the first dot.
+    pub fn team_id(&self) -> TeamId {
+        TeamId(self.split().0.to_owned())
+    }
+
+    /// Returns the bundle id, the part after the first dot.
+    ///
+    /// The bundle id is empty if the application identifier doesn't have a dot.
+    pub fn bundle_id(&self) -> BundleId {
+        BundleId(self.split().1.unwrap_or_default().to_owned())
+    }
+
+    /// Splits the application identifier at the first dot, the bundle id is
+    /// `None` if there is no dot.
+    fn split(&self) -> (&str, Option<&str>) {
+        match self.0.split_once('.') {
+            Some((team_id, bundle_id)) => (team_id, Some(bundle_id)),
+            None => (&self.0, None),
+        }
+    }
+}
+
+impl TeamId {
+    /// Returns the team id as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Returns `true` if the `id` looks like an Apple team id, i.e. it's exactly 10
+/// alphanumeric characters.
+pub fn is_valid_team_id(id: &str) -> bool {
+    id.len() == 10 && id.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+impl BundleId {
+    /// Returns the bundle id as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for AppIdentifier {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<&str> for AppIdentifier {
+    fn from(s: &str) -> Self {
+        Self(s.to_owned())
+    }
+}
+
+impl fmt::Display for AppIdentifier {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for TeamId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl fmt::Display for BundleId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Represents an expiration status of a provisioning profile.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ExpiryStatus {
+    /// The profile has already expired.
+    Expired,
+    /// The profile will expire soon.
+    ExpiringSoon,
+    /// The profile is valid.
+    Valid,
+}
+
+/// Represents a type of a provisioning profile by the way the app is distributed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ProfileType {
+    /// The app can be debugged on provisioned devices.
+    Development,
+    /// The app can be installed on provisioned devices.
+    AdHoc,
+    /// The app is distributed through the App Store.
+    AppStore,
+    /// The app can be installed on any device of the organization.
+    Enterprise,
+}
+
+impl ProfileType {
+    /// All profile types.
+    pub const ALL: &'static [Self] = &[
+        Self::Development,
+        Self::AdHoc,
+        Self::AppStore,
+        Self::Enterprise,
+    ];
+
+    /// Returns a name of the profile type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Development => "development",
+            Self::AdHoc => "adhoc",
+            Self::AppStore => "appstore",
+            Self::Enterprise => "enterprise",
+        }
+    }
+}
+
+impl fmt::Display for ProfileType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ProfileType {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|profile_type| profile_type.as_str() == s)
+            .copied()
+            .ok_or_else(|| {
+                let names = Self::ALL
+                    .iter()
+                    .map(|profile_type| profile_type.as_str())
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown profile type '{}', expected one of: {}",
+                    s,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Represents the `Info` parsed from a data with non-fatal problems.
+///
+/// See [`Info::from_xml_data_partial`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct PartialInfo {
+    pub info: Info,
+    /// Problems of fields that got default values, empty if there are none.
+    pub warnings: Vec<ParseWarning>,
+}
+
+/// Represents a non-fatal problem of parsing the `Info` from a data.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ParseWarning {
+    /// The plist doesn't contain a field.
+    MissingField(&'static str),
+    /// A field of the plist has an unexpected type.
+    InvalidField(&'static str),
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingField(field) => write!(f, "missing field `{}`", field),
+            Self::InvalidField(field) => write!(f, "invalid field `{}`", field),
+        }
+    }
+}
+
+/// Represents criteria of [`Info::matches_filter`], a profile matches if it
+/// meets all of the given ones.
+#[derive(Debug, Default, Clone)]
+pub struct FilterOptions {
+    /// Texts the profile should contain any of, see [`Info::contains`].
+    pub texts: Vec<String>,
+    /// A date by which the profile should expire.
+    pub expires_before: Option<SystemTime>,
+    /// A date after which the profile should expire, e.g. now to skip expired ones.
+    pub expires_after: Option<SystemTime>,
+    /// Patterns the bundle id should match any of, see
+    /// [`Info::matches_bundle_id_patterns`].
+    pub bundle_id_patterns: Vec<glob::Pattern>,
+    /// A UDID of a device the profile should be provisioned for.
+    pub device_udid: Option<String>,
+    /// A platform the profile should be for, see [`Info::is_for_platform`].
+    pub platform: Option<String>,
+}
+
+/// Represents a field of two provisioning profiles compared by [`Info::diff`].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FieldDiff {
+    /// A plist key of the field, nested keys are separated by `.`.
+    pub field: &'static str,
+    pub old: String,
+    pub new: String,
+}
+
+impl FieldDiff {
+    /// Returns `true` if values of the field differ.
+    pub fn is_changed(&self) -> bool {
+        self.old != self.new
+    }
+}
+
+/// Represents an error of parsing the `Info` from a data.
+#[derive(Debug)]
+pub enum InfoError {
+    /// The data doesn't contain a plist.
+    NoPlistFound,
+    /// The plist couldn't be parsed.
+    PlistParseError(plist::Error),
+    /// The plist doesn't contain a required field.
+    MissingField(&'static str),
+}
+
+impl std::error::Error for InfoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::PlistParseError(e) => Some(e),
+            Self::NoPlistFound | Self::MissingField(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for InfoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NoPlistFound => f.write_str("Couldn't find plist content."),
+            Self::PlistParseError(e) => e.fmt(f),
+            Self::MissingField(field) => write!(f, "missing field `{}`", field),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -42,40 +433,458 @@ struct InfoDef {
     pub uuid: String,
     #[serde(rename = "Name")]
     pub name: String,
+    #[serde(rename = "AppIDName", default)]
+    pub app_id_name: String,
     #[serde(rename = "Entitlements")]
     pub entitlements: Entitlements,
+    #[serde(rename = "TeamName", default)]
+    pub team_name: String,
+    #[serde(rename = "TeamIdentifier", default)]
+    pub team_identifier: Vec<String>,
     #[serde(rename = "CreationDate")]
     pub creation_date: plist::Date,
     #[serde(rename = "ExpirationDate")]
     pub expiration_date: plist::Date,
+    #[serde(rename = "DeveloperCertificates", default)]
+    pub developer_certificates: Vec<plist::Data>,
+    /// Absent in profiles for all devices and in some generated by Xcode.
+    #[serde(rename = "ProvisionedDevices")]
+    pub provisioned_devices: Option<Vec<String>>,
+    #[serde(rename = "ProvisionsAllDevices", default)]
+    pub provisions_all_devices: bool,
+    #[serde(rename = "TimeToLive", default)]
+    pub time_to_live: u32,
+    #[serde(rename = "Version", default = "default_version")]
+    pub version: u32,
+    #[serde(rename = "Platform", default)]
+    pub platforms: Vec<String>,
+}
+
+fn default_version() -> u32 {
+    1
+}
+
+#[derive(Debug, Deserialize)]
+struct InfoDefMinimal {
+    #[serde(rename = "UUID")]
+    pub uuid: String,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Entitlements")]
+    pub entitlements: Entitlements,
+    #[serde(rename = "ExpirationDate")]
+    pub expiration_date: plist::Date,
 }
 
 #[derive(Debug, Deserialize)]
 struct Entitlements {
     #[serde(rename = "application-identifier")]
-    pub app_identifier: String,
+    pub app_identifier: AppIdentifierField,
+    #[serde(rename = "get-task-allow", default)]
+    pub get_task_allow: bool,
+}
+
+/// Represents `application-identifier` that some profiles have as an array.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum AppIdentifierField {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl AppIdentifierField {
+    /// Returns the app identifier or the first one if there are many.
+    fn into_first(self) -> Option<String> {
+        match self {
+            Self::One(app_identifier) => Some(app_identifier),
+            Self::Many(app_identifiers) => app_identifiers.into_iter().next(),
+        }
+    }
 }
 
 impl Info {
+    /// Returns an empty profile info, dates are set to the unix epoch.
+    ///
+    /// Handy as a base for the struct update syntax, e.g. in tests.
+    pub fn empty() -> Self {
+        Self {
+            uuid: "".into(),
+            name: "".into(),
+            app_id_name: "".into(),
+            app_identifier: "".into(),
+            team_name: "".into(),
+            team_identifier: TeamId::default(),
+            creation_date: SystemTime::UNIX_EPOCH,
+            expiration_date: SystemTime::UNIX_EPOCH,
+            developer_certificates: Vec::new(),
+            provisioned_devices: Vec::new(),
+            provisions_all_devices: false,
+            get_task_allow: false,
+            platforms: Vec::new(),
+            time_to_live: 0,
+            version: 1,
+        }
+    }
+
+    /// Returns instance of the `Info` parsed from a file.
+    ///
+    /// The same as [`Profile::from_file`] for callers that don't need the path.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Self::parse(path, &read_file(path)?)
+    }
+
+    /// Parses the content of a file at `path`, the path is only used for errors.
+    fn parse(path: &Path, data: &[u8]) -> Result<Self> {
+        Self::from_xml_data(data).map_err(|source| Error::Parse {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
     /// Returns instance of the `Info` parsed from a `data`.
-    pub fn from_xml_data(data: &[u8]) -> Option<Self> {
-        crate::plist_extractor::find(data).and_then(|xml| {
-            plist::from_reader_xml(io::Cursor::new(xml))
-                .ok()
-                .map(|info: InfoDef| Self {
-                    uuid: info.uuid,
-                    name: info.name,
-                    app_identifier: info.entitlements.app_identifier,
-                    creation_date: info.creation_date.into(),
-                    expiration_date: info.expiration_date.into(),
+    pub fn from_xml_data(data: &[u8]) -> std::result::Result<Self, InfoError> {
+        let info = crate::plist_extractor::find(data)
+            .ok_or(InfoError::NoPlistFound)
+            .and_then(|xml| {
+                Self::parse_clean_xml(&crate::plist_extractor::normalize_line_endings(xml))
+            });
+        #[cfg(feature = "tracing")]
+        match &info {
+            Ok(info) => tracing::debug!(uuid = %info.uuid, name = %info.name, "parsed profile"),
+            Err(err) => tracing::warn!(
+                len = data.len(),
+                prefix = %data.iter().take(100).map(|byte| format!("{:02x}", byte)).collect::<String>(),
+                error = %err,
+                "failed to parse profile"
+            ),
+        }
+        info
+    }
+
+    /// Returns instance of the `Info` parsed from a `data` or `None`.
+    ///
+    /// The same as [`Info::from_xml_data`] but it drops the error.
+    pub fn from_xml_data_opt(data: &[u8]) -> Option<Self> {
+        Self::from_xml_data(data).ok()
+    }
+
+    /// Returns instance of the `MinimalInfo` parsed from a `data`.
+    ///
+    /// Unlike [`Info::from_xml_data`] it skips fields that are not needed to list
+    /// profiles, e.g. developer certificates and devices.
+    pub fn from_xml_data_minimal(data: &[u8]) -> Option<MinimalInfo> {
+        let xml =
+            crate::plist_extractor::normalize_line_endings(crate::plist_extractor::find(data)?);
+        let info: InfoDefMinimal = plist::from_reader_xml(io::Cursor::new(xml)).ok()?;
+        Some(MinimalInfo {
+            uuid: info.uuid,
+            name: info.name,
+            app_identifier: info.entitlements.app_identifier.into_first()?.into(),
+            expiration_date: info.expiration_date.into(),
+        })
+    }
+
+    /// Returns instance of the `Info` parsed from an already extracted plist `xml`.
+    ///
+    /// Unlike [`Info::from_xml_data`] it doesn't search for a plist content in the data,
+    /// so it's suitable to parse the output of [`crate::show`].
+    pub fn from_clean_xml(xml: &[u8]) -> Result<Self> {
+        Self::parse_clean_xml(xml)
+            .map_err(|err| Error::Own(format!("Couldn't parse plist: {}", err)))
+    }
+
+    fn parse_clean_xml(xml: &[u8]) -> std::result::Result<Self, InfoError> {
+        let info: InfoDef =
+            plist::from_reader_xml(io::Cursor::new(xml)).map_err(InfoError::PlistParseError)?;
+        Self::from_def(info)
+    }
+
+    /// Returns instance of the `PartialInfo` parsed from a `data`.
+    ///
+    /// Unlike [`Info::from_xml_data`] it tolerates fields that are not needed to
+    /// identify a profile having unexpected types. Such fields get default values
+    /// (dates get the unix epoch) and are reported as warnings.
+    pub fn from_xml_data_partial(data: &[u8]) -> std::result::Result<PartialInfo, InfoError> {
+        let xml = crate::plist_extractor::normalize_line_endings(
+            crate::plist_extractor::find(data).ok_or(InfoError::NoPlistFound)?,
+        );
+        let err = match Self::parse_clean_xml(&xml) {
+            Ok(info) => {
+                return Ok(PartialInfo {
+                    info,
+                    warnings: Vec::new(),
                 })
+            }
+            Err(err) => err,
+        };
+        let Ok(plist::Value::Dictionary(mut dict)) =
+            plist::Value::from_reader_xml(io::Cursor::new(&xml[..]))
+        else {
+            return Err(err);
+        };
+        type Validator = fn(&plist::Value) -> bool;
+        fn is_valid<T: serde::de::DeserializeOwned>(value: &plist::Value) -> bool {
+            plist::from_value::<T>(value).is_ok()
+        }
+        let optional_fields: [(&'static str, Validator); 9] = [
+            ("AppIDName", is_valid::<String>),
+            ("TeamName", is_valid::<String>),
+            ("TeamIdentifier", is_valid::<Vec<String>>),
+            ("DeveloperCertificates", is_valid::<Vec<plist::Data>>),
+            ("ProvisionedDevices", is_valid::<Vec<String>>),
+            ("ProvisionsAllDevices", is_valid::<bool>),
+            ("TimeToLive", is_valid::<u32>),
+            ("Version", is_valid::<u32>),
+            ("Platform", is_valid::<Vec<String>>),
+        ];
+        let mut warnings = Vec::new();
+        for (field, is_valid) in optional_fields {
+            if dict.get(field).is_some_and(|value| !is_valid(value)) {
+                dict.remove(field);
+                warnings.push(ParseWarning::InvalidField(field));
+            }
+        }
+        for field in ["CreationDate", "ExpirationDate"] {
+            let warning = match dict.get(field) {
+                None => ParseWarning::MissingField(field),
+                Some(value) if !is_valid::<plist::Date>(value) => ParseWarning::InvalidField(field),
+                Some(_) => continue,
+            };
+            dict.insert(
+                field.to_owned(),
+                plist::Date::from(SystemTime::UNIX_EPOCH).into(),
+            );
+            warnings.push(warning);
+        }
+        let info = plist::from_value::<InfoDef>(&plist::Value::Dictionary(dict))
+            .map_err(|_| err)
+            .and_then(Self::from_def)?;
+        Ok(PartialInfo { info, warnings })
+    }
+
+    fn from_def(info: InfoDef) -> std::result::Result<Self, InfoError> {
+        let mut provisioned_devices = info.provisioned_devices.unwrap_or_default();
+        // Some profiles contain duplicated devices.
+        provisioned_devices.sort_unstable();
+        provisioned_devices.dedup();
+        let app_identifier = AppIdentifier::from(
+            info.entitlements
+                .app_identifier
+                .into_first()
+                .ok_or(InfoError::MissingField("application-identifier"))?,
+        );
+        // Older profiles don't have a team identifier.
+        let team_identifier = match info.team_identifier.into_iter().next() {
+            Some(team_identifier) if !team_identifier.is_empty() => TeamId(team_identifier),
+            _ => app_identifier.team_id(),
+        };
+        Ok(Self {
+            uuid: info.uuid,
+            name: info.name,
+            app_id_name: info.app_id_name,
+            app_identifier,
+            team_name: info.team_name,
+            team_identifier,
+            creation_date: info.creation_date.into(),
+            expiration_date: info.expiration_date.into(),
+            developer_certificates: info
+                .developer_certificates
+                .into_iter()
+                .map(Vec::from)
+                .collect(),
+            provisioned_devices,
+            provisions_all_devices: info.provisions_all_devices,
+            get_task_allow: info.entitlements.get_task_allow,
+            platforms: info.platforms,
+            time_to_live: info.time_to_live,
+            version: info.version,
         })
     }
 
+    /// Returns a plist dictionary reconstructed from the fields of the `Info`.
+    ///
+    /// The dictionary contains only keys that are known to the `Info`, so it can
+    /// be parsed back using [`Info::from_clean_xml`] after it's written as xml.
+    pub fn to_plist_dict(&self) -> plist::Dictionary {
+        let mut entitlements = plist::Dictionary::new();
+        entitlements.insert(
+            "application-identifier".to_owned(),
+            self.app_identifier.as_str().into(),
+        );
+        if self.get_task_allow {
+            entitlements.insert("get-task-allow".to_owned(), true.into());
+        }
+        let developer_certificates = self
+            .developer_certificates
+            .iter()
+            .map(|certificate| plist::Value::Data(certificate.clone()))
+            .collect::<Vec<_>>();
+        let mut dict = plist::Dictionary::new();
+        dict.insert("UUID".to_owned(), self.uuid.clone().into());
+        dict.insert("Name".to_owned(), self.name.clone().into());
+        if !self.app_id_name.is_empty() {
+            dict.insert("AppIDName".to_owned(), self.app_id_name.clone().into());
+        }
+        dict.insert("Entitlements".to_owned(), entitlements.into());
+        if !self.team_name.is_empty() {
+            dict.insert("TeamName".to_owned(), self.team_name.clone().into());
+        }
+        if !self.team_identifier.0.is_empty() {
+            dict.insert(
+                "TeamIdentifier".to_owned(),
+                vec![plist::Value::from(self.team_identifier.0.clone())].into(),
+            );
+        }
+        dict.insert(
+            "CreationDate".to_owned(),
+            plist::Date::from(self.creation_date).into(),
+        );
+        dict.insert(
+            "ExpirationDate".to_owned(),
+            plist::Date::from(self.expiration_date).into(),
+        );
+        dict.insert(
+            "DeveloperCertificates".to_owned(),
+            developer_certificates.into(),
+        );
+        if !self.provisioned_devices.is_empty() {
+            let devices = self
+                .provisioned_devices
+                .iter()
+                .map(|device| plist::Value::String(device.clone()))
+                .collect::<Vec<_>>();
+            dict.insert("ProvisionedDevices".to_owned(), devices.into());
+        }
+        if self.provisions_all_devices {
+            dict.insert("ProvisionsAllDevices".to_owned(), true.into());
+        }
+        if self.time_to_live != 0 {
+            dict.insert("TimeToLive".to_owned(), self.time_to_live.into());
+        }
+        dict.insert("Version".to_owned(), self.version.into());
+        if !self.platforms.is_empty() {
+            let platforms = self
+                .platforms
+                .iter()
+                .map(|platform| plist::Value::String(platform.clone()))
+                .collect::<Vec<_>>();
+            dict.insert("Platform".to_owned(), platforms.into());
+        }
+        dict
+    }
+
+    /// Returns an expiration status of the profile relative to the current time.
+    ///
+    /// A profile is considered as expiring soon if it expires within `warn_days`.
+    pub fn expiry_status(&self, warn_days: u64) -> ExpiryStatus {
+        let now = SystemTime::now();
+        // A threshold that can't be represented is in the far future.
+        let warn_date = warn_days
+            .checked_mul(24 * 60 * 60)
+            .and_then(|secs| now.checked_add(Duration::from_secs(secs)));
+        if self.expiration_date <= now {
+            ExpiryStatus::Expired
+        } else if warn_date.is_none_or(|date| self.expiration_date <= date) {
+            ExpiryStatus::ExpiringSoon
+        } else {
+            ExpiryStatus::Valid
+        }
+    }
+
+    /// Returns the number of devices the profile is provisioned for.
+    ///
+    /// Returns `None` if the profile is provisioned for all devices (enterprise
+    /// profiles) and `Some(0)` if it's not provisioned for any device (App Store
+    /// profiles).
+    pub fn provisioned_device_count(&self) -> Option<usize> {
+        if self.provisions_all_devices {
+            None
+        } else {
+            Some(self.provisioned_devices.len())
+        }
+    }
+
+    /// Returns a type of the profile.
+    ///
+    /// Profiles for all devices are enterprise ones, profiles without devices are
+    /// App Store ones, the rest are development ones if they allow debugging and
+    /// ad hoc ones otherwise.
+    pub fn profile_type(&self) -> ProfileType {
+        if self.provisions_all_devices {
+            ProfileType::Enterprise
+        } else if self.provisioned_devices.is_empty() {
+            ProfileType::AppStore
+        } else if self.get_task_allow {
+            ProfileType::Development
+        } else {
+            ProfileType::AdHoc
+        }
+    }
+
+    /// Returns intended validity duration of the profile in days.
+    ///
+    /// Returns `0` if the profile doesn't specify it.
+    pub fn time_to_live_days(&self) -> u32 {
+        self.time_to_live
+    }
+
+    /// Returns a version of the profile format.
+    ///
+    /// Returns `1` if the profile doesn't specify it.
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    /// Checks that the expiration date of the profile is plausible.
+    ///
+    /// Apple limits validity of profiles to a year, so a profile that expires more
+    /// than 5 years before its creation or more than 5 years ago is most likely
+    /// corrupted.
+    #[cfg(feature = "strict-validation")]
+    pub fn sanity_check(&self) -> std::result::Result<(), ValidationError> {
+        const FIVE_YEARS: Duration = Duration::from_secs(5 * 365 * 24 * 60 * 60);
+        let is_before = |date: SystemTime| {
+            date.checked_sub(FIVE_YEARS)
+                .is_some_and(|date| self.expiration_date < date)
+        };
+        if is_before(self.creation_date) || is_before(SystemTime::now()) {
+            Err(ValidationError::AncientExpiry)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Returns the UTC date on which the profile was created.
+    pub fn creation_day(&self) -> time::Date {
+        OffsetDateTime::from(self.creation_date).date()
+    }
+
+    /// Returns `true` if both profiles were created on the same UTC date.
+    pub fn created_on_same_day_as(&self, other: &Info) -> bool {
+        self.creation_day() == other.creation_day()
+    }
+
+    /// Returns the number of whole days between the creation and the expiration dates.
+    ///
+    /// Returns `0` if the profile expires before it was created.
+    pub fn duration_days(&self) -> u64 {
+        self.expiration_date
+            .duration_since(self.creation_date)
+            .unwrap_or_default()
+            .as_secs()
+            / 86400
+    }
+
     /// Returns `true` if one or more fields of the profile contain `string`.
     pub fn contains(&self, string: &str) -> bool {
         let s = string.to_lowercase();
-        let items = &[&self.name, &self.app_identifier, &self.uuid];
+        let items = [
+            self.name.as_str(),
+            self.app_id_name.as_str(),
+            self.app_identifier.as_str(),
+            self.team_name.as_str(),
+            self.uuid.as_str(),
+        ];
         for item in items {
             if item.to_lowercase().contains(&s) {
                 return true;
@@ -84,18 +893,201 @@ impl Info {
         false
     }
 
-    /// Returns `true` if the profile has any of `ids` as `uuid` or `bundle_id`.
+    /// Returns `true` if the profile has any of `ids` as `uuid`, `bundle_id` or `name`.
+    ///
+    /// The `name` is matched as well since it's easy to confuse with an id. The
+    /// `bundle_id` is matched case-insensitively, see [`Info::normalized_bundle_id`].
     pub fn has_ids(&self, ids: impl IntoIterator<Item = impl AsRef<str>>) -> bool {
-        let bundle_id = self.bundle_id();
-        ids.into_iter()
-            .any(|id| self.uuid == id.as_ref() || bundle_id == Some(id.as_ref()))
+        self.has_ids_with(ids, str::eq)
+    }
+
+    /// Returns `true` if the profile has any of `ids` as `uuid`, `bundle_id` or `name`
+    /// using a `comparator` to compare them.
+    ///
+    /// The same as [`Info::has_ids`] but allows fuzzy matching, e.g. with
+    /// `str::eq_ignore_ascii_case`. The `comparator` takes a field of the profile
+    /// and an id, the `bundle_id` is still lowercased on both sides.
+    pub fn has_ids_with<F>(
+        &self,
+        ids: impl IntoIterator<Item = impl AsRef<str>>,
+        comparator: F,
+    ) -> bool
+    where
+        F: Fn(&str, &str) -> bool,
+    {
+        let bundle_id = self.normalized_bundle_id();
+        ids.into_iter().any(|id| {
+            let id = id.as_ref();
+            comparator(&self.uuid, id)
+                || bundle_id
+                    .as_deref()
+                    .is_some_and(|bundle_id| comparator(bundle_id, &id.to_lowercase()))
+                || comparator(&self.name, id)
+        })
+    }
+
+    /// Returns `true` if the bundle id of the profile matches any of `patterns`.
+    ///
+    /// Patterns are matched case-insensitively.
+    pub fn matches_bundle_id_patterns(&self, patterns: &[glob::Pattern]) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..glob::MatchOptions::new()
+        };
+        self.bundle_id().is_some_and(|bundle_id| {
+            patterns
+                .iter()
+                .any(|pattern| pattern.matches_with(bundle_id, options))
+        })
+    }
+
+    /// Returns `true` if the profile meets all criteria of `opts`.
+    ///
+    /// Empty criteria are skipped, so a profile always matches default options.
+    /// A profile provisioned for all devices matches any `device_udid`.
+    pub fn matches_filter(&self, opts: &FilterOptions) -> bool {
+        (opts.texts.is_empty() || opts.texts.iter().any(|text| self.contains(text)))
+            && opts
+                .expires_before
+                .is_none_or(|date| self.expiration_date <= date)
+            && opts
+                .expires_after
+                .is_none_or(|date| self.expiration_date > date)
+            && (opts.bundle_id_patterns.is_empty()
+                || self.matches_bundle_id_patterns(&opts.bundle_id_patterns))
+            && opts.device_udid.as_ref().is_none_or(|udid| {
+                self.provisions_all_devices || self.provisioned_devices.binary_search(udid).is_ok()
+            })
+            && opts
+                .platform
+                .as_ref()
+                .is_none_or(|platform| self.is_for_platform(platform))
+    }
+
+    /// Returns `true` if the profile is for the `platform`, e.g. `ios` or `macos`.
+    ///
+    /// Platforms are compared case-insensitively and `macos` matches `OSX` that
+    /// macOS profiles have.
+    pub fn is_for_platform(&self, platform: &str) -> bool {
+        let normalize = |platform: &str| {
+            let platform = platform.to_lowercase();
+            if platform == "osx" {
+                "macos".to_owned()
+            } else {
+                platform
+            }
+        };
+        let platform = normalize(platform);
+        self.platforms.iter().any(|p| normalize(p) == platform)
+    }
+
+    /// Returns `true` if the uuid consists of hex digits and `-` only, so it's
+    /// safe to use as a file name.
+    pub fn has_valid_uuid(&self) -> bool {
+        !self.uuid.is_empty() && self.uuid.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
     }
 
     /// Returns a bundle id of a profile.
     pub fn bundle_id(&self) -> Option<&str> {
-        self.app_identifier
-            .find(|ch| ch == '.')
-            .map(|i| &self.app_identifier[(i + 1)..])
+        self.app_identifier.split().1
+    }
+
+    /// Returns a lowercased bundle id of a profile for comparisons.
+    pub fn normalized_bundle_id(&self) -> Option<String> {
+        self.bundle_id().map(|s| s.to_lowercase())
+    }
+
+    /// Compares fields of the profile with fields of the `other` one.
+    ///
+    /// All fields are returned in the same order, use [`FieldDiff::is_changed`]
+    /// to keep only the changed ones.
+    pub fn diff(&self, other: &Info) -> Vec<FieldDiff> {
+        let fields = |info: &Info| {
+            // Certificates are compared by their fingerprints, so a rotated
+            // certificate is reported even if the number of them is the same.
+            let mut fingerprints = info
+                .developer_certificates
+                .iter()
+                .map(|certificate| crate::sha256(certificate))
+                .collect::<Vec<_>>();
+            fingerprints.sort();
+            [
+                ("UUID", info.uuid.clone()),
+                ("Name", info.name.clone()),
+                ("AppIDName", info.app_id_name.clone()),
+                (
+                    "Entitlements.application-identifier",
+                    info.app_identifier.to_string(),
+                ),
+                ("TeamName", info.team_name.clone()),
+                ("TeamIdentifier", info.team_identifier.to_string()),
+                ("CreationDate", format_date(info.creation_date)),
+                ("ExpirationDate", format_date(info.expiration_date)),
+                ("DeveloperCertificates", fingerprints.join(", ")),
+                ("ProvisionedDevices", info.provisioned_devices.join(", ")),
+                (
+                    "ProvisionsAllDevices",
+                    info.provisions_all_devices.to_string(),
+                ),
+                (
+                    "Entitlements.get-task-allow",
+                    info.get_task_allow.to_string(),
+                ),
+                ("TimeToLive", info.time_to_live.to_string()),
+                ("Version", info.version.to_string()),
+                ("Platform", info.platforms.join(", ")),
+            ]
+        };
+        fields(self)
+            .into_iter()
+            .zip(fields(other))
+            .map(|((field, old), (_, new))| FieldDiff { field, old, new })
+            .collect()
+    }
+}
+
+/// A format of dates in the human-readable output.
+const DATE_FORMAT: &[FormatItem] =
+    format_description!("[year]-[month]-[day] [hour]:[minute]:[second] UTC");
+
+/// Formats the `date` using the `DATE_FORMAT`.
+fn format_date(date: SystemTime) -> String {
+    OffsetDateTime::from(date)
+        .format(DATE_FORMAT)
+        .unwrap_or_default()
+}
+
+/// Renders the info the same way `mprovision list` does by default but without
+/// colors, so `println!("{}", info)` can be used with any terminal styling.
+impl fmt::Display for Info {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format_date = |date: SystemTime| {
+            OffsetDateTime::from(date)
+                .format(DATE_FORMAT)
+                .map_err(|_| fmt::Error)
+        };
+        writeln!(f, "{}", self.uuid)?;
+        writeln!(f, "{}", self.app_identifier)?;
+        writeln!(f, "{}", self.name)?;
+        if !self.app_id_name.is_empty() {
+            writeln!(f, "App ID: {}", self.app_id_name)?;
+        }
+        match self.provisioned_device_count() {
+            Some(1) => writeln!(f, "1 device")?,
+            Some(count) => writeln!(f, "{} devices", count)?,
+            None => writeln!(f, "All devices")?,
+        }
+        write!(
+            f,
+            "{} - {}",
+            format_date(self.creation_date)?,
+            format_date(self.expiration_date)?
+        )?;
+        match self.time_to_live_days() {
+            0 => Ok(()),
+            1 => write!(f, "\nValid for: 1 day total"),
+            days => write!(f, "\nValid for: {} days total", days),
+        }
     }
 }
 
@@ -103,17 +1095,116 @@ impl Info {
 mod tests {
     use super::*;
 
-    impl Info {
-        /// Returns an empty profile info.
-        fn empty() -> Self {
-            Self {
-                uuid: "".into(),
-                name: "".into(),
-                app_identifier: "".into(),
-                creation_date: SystemTime::UNIX_EPOCH,
-                expiration_date: SystemTime::UNIX_EPOCH,
-            }
-        }
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_profile() {
+        let path = PathBuf::from("/profiles/1.mobileprovision");
+        let profile = Profile {
+            path: path.clone(),
+            info: Info::empty(),
+        };
+        let json = serde_json::to_value(&profile).unwrap();
+        assert_eq!(PathBuf::from(json["path"].as_str().unwrap()), path);
+        assert_eq!(json["info"]["uuid"], "");
+    }
+
+    #[test]
+    fn info_from_file() {
+        let info = Info::from_file(Path::new("tests/test.xml")).unwrap();
+        assert_eq!(info.uuid, "fbcdef0a-af78-4a11-b1f1-87cb897bfa8e");
+        assert_eq!(
+            Profile::from_file(Path::new("tests/test.xml"))
+                .unwrap()
+                .info,
+            info
+        );
+    }
+
+    #[test]
+    fn info_from_missing_file() {
+        let path = Path::new("tests/missing.xml");
+        assert!(matches!(
+            Info::from_file(path),
+            Err(Error::FileIo { path: error_path, .. }) if error_path == path
+        ));
+    }
+
+    #[test]
+    fn sort_profiles_by_expiration_date() {
+        let profile = |uuid: &str, days: u64| Profile {
+            path: PathBuf::from(format!("{}.mobileprovision", uuid)),
+            info: Info {
+                uuid: uuid.into(),
+                expiration_date: SystemTime::UNIX_EPOCH + Duration::from_secs(days * 24 * 60 * 60),
+                ..Info::empty()
+            },
+        };
+        let mut profiles = [profile("a", 30), profile("b", 10), profile("c", 20)];
+        profiles.sort();
+        let uuids = profiles
+            .iter()
+            .map(|profile| profile.info.uuid.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(uuids, vec!["b", "c", "a"]);
+        assert_eq!(profile("a", 1), profile("a", 1));
+        assert_ne!(profile("a", 1), profile("b", 1));
+    }
+
+    #[test]
+    fn duration_days() {
+        let day = Duration::from_secs(24 * 60 * 60);
+        let info = |creation_days: u32, expiration_days: u32| Info {
+            creation_date: SystemTime::UNIX_EPOCH + day * creation_days,
+            expiration_date: SystemTime::UNIX_EPOCH + day * expiration_days,
+            ..Info::empty()
+        };
+        assert_eq!(info(0, 365).duration_days(), 365);
+        assert_eq!(info(10, 376).duration_days(), 366);
+        assert_eq!(info(10, 5).duration_days(), 0);
+        assert_eq!(info(10, 10).duration_days(), 0);
+    }
+
+    #[cfg(feature = "strict-validation")]
+    #[test]
+    fn sanity_check() {
+        let year = Duration::from_secs(365 * 24 * 60 * 60);
+        let now = SystemTime::now();
+        let info = |creation_date: SystemTime, expiration_date: SystemTime| Info {
+            creation_date,
+            expiration_date,
+            ..Info::empty()
+        };
+        assert_eq!(info(now - year, now + year).sanity_check(), Ok(()));
+        assert_eq!(info(now - year * 5, now - year * 4).sanity_check(), Ok(()));
+        assert_eq!(
+            info(now - year * 7, now - year * 6).sanity_check(),
+            Err(ValidationError::AncientExpiry)
+        );
+        assert_eq!(
+            info(now, now - year * 6).sanity_check(),
+            Err(ValidationError::AncientExpiry)
+        );
+    }
+
+    #[test]
+    fn app_identifier_parts() {
+        let app_identifier = AppIdentifier::from("12345ABCDE.com.example.app");
+        assert_eq!(app_identifier.team_id(), TeamId("12345ABCDE".into()));
+        assert_eq!(
+            app_identifier.bundle_id(),
+            BundleId("com.example.app".into())
+        );
+        let app_identifier = AppIdentifier::from("12345ABCDE");
+        assert_eq!(app_identifier.team_id().as_str(), "12345ABCDE");
+        assert_eq!(app_identifier.bundle_id().as_str(), "");
+    }
+
+    #[test]
+    fn valid_team_id() {
+        assert!(is_valid_team_id("ABCDE12345"));
+        assert!(!is_valid_team_id("too-short"));
+        assert!(!is_valid_team_id("has spaces!"));
+        assert!(!is_valid_team_id("TOOLONG12345"));
     }
 
     #[test]
@@ -121,53 +1212,354 @@ mod tests {
         let profile = Info {
             uuid: "123".into(),
             name: "name".into(),
+            app_id_name: "App Development".into(),
             app_identifier: "id".into(),
-            creation_date: SystemTime::UNIX_EPOCH,
-            expiration_date: SystemTime::UNIX_EPOCH,
+            team_name: "My Company, Inc".into(),
+            ..Info::empty()
         };
         assert!(profile.contains("12"));
         assert!(profile.contains("me"));
         assert!(profile.contains("id"));
+        assert!(profile.contains("app dev"));
+        assert!(profile.contains("company"));
+    }
+
+    #[test]
+    fn display() {
+        let info = Info {
+            uuid: "123".into(),
+            name: "name".into(),
+            app_id_name: "App".into(),
+            app_identifier: "TEAM.com.example.app".into(),
+            provisioned_devices: vec!["abc".into()],
+            ..Info::empty()
+        };
+        assert_eq!(
+            info.to_string(),
+            "123\nTEAM.com.example.app\nname\nApp ID: App\n1 device\n\
+             1970-01-01 00:00:00 UTC - 1970-01-01 00:00:00 UTC"
+        );
+    }
+
+    #[test]
+    fn display_fields() {
+        let info = Info {
+            uuid: "123".into(),
+            name: "name".into(),
+            app_identifier: "TEAM.com.example.app".into(),
+            creation_date: SystemTime::UNIX_EPOCH + Duration::from_secs(24 * 60 * 60),
+            expiration_date: SystemTime::UNIX_EPOCH + Duration::from_secs(48 * 60 * 60),
+            time_to_live: 1,
+            ..Info::empty()
+        };
+        let text = info.to_string();
+        for field in [
+            "123",
+            "TEAM.com.example.app",
+            "name",
+            "1970-01-02 00:00:00 UTC - 1970-01-03 00:00:00 UTC",
+            "Valid for: 1 day total",
+        ] {
+            assert!(text.contains(field), "{} is missing in {}", field, text);
+        }
+        assert!(!text.contains('\x1b'));
+    }
+
+    #[test]
+    fn created_on_same_day() {
+        let mut profile = Info::empty();
+        profile.creation_date = SystemTime::UNIX_EPOCH + Duration::from_secs(60);
+        let mut other = Info::empty();
+        other.creation_date = SystemTime::UNIX_EPOCH + Duration::from_secs(24 * 60 * 60 - 1);
+        assert!(profile.created_on_same_day_as(&other));
+        other.creation_date += Duration::from_secs(1);
+        assert!(!profile.created_on_same_day_as(&other));
+    }
+
+    #[test]
+    fn expired() {
+        let profile = Info::empty();
+        assert_eq!(profile.expiry_status(30), ExpiryStatus::Expired);
+    }
+
+    #[test]
+    fn expiring_soon() {
+        let mut profile = Info::empty();
+        profile.expiration_date = SystemTime::now() + Duration::from_secs(24 * 60 * 60);
+        assert_eq!(profile.expiry_status(30), ExpiryStatus::ExpiringSoon);
+        assert_eq!(profile.expiry_status(0), ExpiryStatus::Valid);
+        assert_eq!(profile.expiry_status(u64::MAX), ExpiryStatus::ExpiringSoon);
+    }
+
+    #[test]
+    fn valid() {
+        let mut profile = Info::empty();
+        profile.expiration_date = SystemTime::now() + Duration::from_secs(60 * 24 * 60 * 60);
+        assert_eq!(profile.expiry_status(30), ExpiryStatus::Valid);
+    }
+
+    #[test]
+    fn provisioned_device_count() {
+        let mut profile = Info::empty();
+        assert_eq!(profile.provisioned_device_count(), Some(0));
+        profile.provisioned_devices = vec!["a".to_owned(), "b".to_owned()];
+        assert_eq!(profile.provisioned_device_count(), Some(2));
+        profile.provisions_all_devices = true;
+        assert_eq!(profile.provisioned_device_count(), None);
     }
 
     #[test]
     fn has_id_in_bundle_id() {
         let mut profile = Info::empty();
-        profile.app_identifier = "12345ABCDE.com.example.app".to_owned();
-        assert!(profile.has_ids(&["com.example.app"]));
+        profile.app_identifier = "12345ABCDE.com.example.app".into();
+        assert!(profile.has_ids(["com.example.app"]));
+    }
+
+    #[test]
+    fn has_id_in_bundle_id_ignoring_case() {
+        let mut profile = Info::empty();
+        profile.app_identifier = "12345ABCDE.com.example.app".into();
+        assert!(profile.has_ids(["COM.EXAMPLE.APP"]));
+        assert_eq!(
+            profile.normalized_bundle_id(),
+            Some("com.example.app".to_owned())
+        );
+    }
+
+    #[test]
+    fn matches_bundle_id_patterns() {
+        let mut profile = Info::empty();
+        profile.app_identifier = "12345ABCDE.com.example.app".into();
+        let pattern = |s| glob::Pattern::new(s).unwrap();
+        assert!(profile.matches_bundle_id_patterns(&[pattern("com.example.*")]));
+        assert!(profile.matches_bundle_id_patterns(&[pattern("COM.EXAMPLE.ap?")]));
+        assert!(!profile.matches_bundle_id_patterns(&[pattern("com.other.*")]));
     }
 
     #[test]
     fn has_id_in_uuid() {
         let mut profile = Info::empty();
         profile.uuid = String::from("123");
-        assert!(profile.has_ids(&["123"]));
+        assert!(profile.has_ids(["123"]));
+    }
+
+    #[test]
+    fn has_id_in_name() {
+        let mut profile = Info::empty();
+        profile.name = String::from("iOS Team Provisioning Profile");
+        assert!(profile.has_ids(["iOS Team Provisioning Profile"]));
+        assert!(!profile.has_ids(["iOS Team"]));
+    }
+
+    #[test]
+    fn has_ids_with_comparator() {
+        let mut profile = Info::empty();
+        profile.uuid = String::from("ABCDEF12-3456");
+        profile.app_identifier = "12345ABCDE.com.example.app".into();
+        assert!(profile.has_ids_with(["ABCDEF12-3456"], str::eq));
+        assert!(!profile.has_ids_with(["abcdef12-3456"], str::eq));
+        assert!(profile.has_ids_with(["abcdef12-3456"], str::eq_ignore_ascii_case));
+        assert!(profile.has_ids_with(["COM.EXAMPLE.APP"], str::eq));
+        assert!(!profile.has_ids_with(["com.example"], str::eq_ignore_ascii_case));
+        assert!(profile.has_ids_with(["com.example"], |field, id| field.starts_with(id)));
     }
 
     #[test]
     fn does_not_have_ids() {
         let profile = Info::empty();
-        assert!(!profile.has_ids(&["a", "b", "c"]));
+        assert!(!profile.has_ids(["a", "b", "c"]));
     }
 
     #[test]
     fn correct_bundle_id() {
         let mut profile = Info::empty();
-        profile.app_identifier = "12345ABCDE.com.example.app".to_owned();
+        profile.app_identifier = "12345ABCDE.com.example.app".into();
         assert_eq!(profile.bundle_id(), Some("com.example.app"));
     }
 
     #[test]
     fn incorrect_bundle_id() {
         let mut profile = Info::empty();
-        profile.app_identifier = "12345ABCDE".to_owned();
+        profile.app_identifier = "12345ABCDE".into();
         assert_eq!(profile.bundle_id(), None);
     }
 
     #[test]
     fn wildcard_bundle_id() {
         let mut profile = Info::empty();
-        profile.app_identifier = "12345ABCDE.*".to_owned();
+        profile.app_identifier = "12345ABCDE.*".into();
         assert_eq!(profile.bundle_id(), Some("*"));
     }
+
+    #[test]
+    fn matches_filter_by_texts() {
+        let mut profile = Info::empty();
+        profile.name = "Example Development".into();
+        let opts = |texts: &[&str]| FilterOptions {
+            texts: texts.iter().map(|text| text.to_string()).collect(),
+            ..FilterOptions::default()
+        };
+        assert!(profile.matches_filter(&opts(&["other", "example"])));
+        assert!(!profile.matches_filter(&opts(&["other"])));
+    }
+
+    #[test]
+    fn matches_filter_by_expiration_date() {
+        let mut profile = Info::empty();
+        profile.expiration_date = SystemTime::UNIX_EPOCH + Duration::from_secs(100);
+        let opts = |secs: u64| FilterOptions {
+            expires_before: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            ..FilterOptions::default()
+        };
+        assert!(profile.matches_filter(&opts(100)));
+        assert!(!profile.matches_filter(&opts(99)));
+        let opts = |secs: u64| FilterOptions {
+            expires_after: Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            ..FilterOptions::default()
+        };
+        assert!(profile.matches_filter(&opts(99)));
+        assert!(!profile.matches_filter(&opts(100)));
+    }
+
+    #[test]
+    fn matches_filter_by_bundle_id_patterns() {
+        let mut profile = Info::empty();
+        profile.app_identifier = "TEAM.com.example.app".into();
+        let opts = |pattern: &str| FilterOptions {
+            bundle_id_patterns: vec![glob::Pattern::new(pattern).unwrap()],
+            ..FilterOptions::default()
+        };
+        assert!(profile.matches_filter(&opts("com.example.*")));
+        assert!(!profile.matches_filter(&opts("org.example.*")));
+    }
+
+    #[test]
+    fn matches_filter_by_device_udid() {
+        let mut profile = Info::empty();
+        profile.provisioned_devices = vec!["a".into(), "b".into()];
+        let opts = |udid: &str| FilterOptions {
+            device_udid: Some(udid.into()),
+            ..FilterOptions::default()
+        };
+        assert!(profile.matches_filter(&opts("b")));
+        assert!(!profile.matches_filter(&opts("c")));
+        profile.provisions_all_devices = true;
+        assert!(profile.matches_filter(&opts("c")));
+    }
+
+    #[test]
+    fn matches_filter_by_platform() {
+        let mut profile = Info::empty();
+        profile.platforms = vec!["iOS".into(), "OSX".into()];
+        let opts = |platform: &str| FilterOptions {
+            platform: Some(platform.into()),
+            ..FilterOptions::default()
+        };
+        assert!(profile.matches_filter(&opts("ios")));
+        assert!(profile.matches_filter(&opts("macos")));
+        assert!(!profile.matches_filter(&opts("tvos")));
+    }
+
+    #[test]
+    fn matches_filter_by_all_options() {
+        let mut profile = Info::empty();
+        profile.name = "Example".into();
+        profile.app_identifier = "TEAM.com.example.app".into();
+        profile.provisioned_devices = vec!["a".into()];
+        assert!(profile.matches_filter(&FilterOptions::default()));
+        let mut opts = FilterOptions {
+            texts: vec!["example".into()],
+            expires_before: Some(SystemTime::UNIX_EPOCH),
+            expires_after: None,
+            bundle_id_patterns: vec![glob::Pattern::new("com.example.*").unwrap()],
+            device_udid: Some("a".into()),
+            platform: None,
+        };
+        assert!(profile.matches_filter(&opts));
+        opts.device_udid = Some("b".into());
+        assert!(!profile.matches_filter(&opts));
+    }
+
+    #[test]
+    fn profile_types() {
+        let mut profile = Info::empty();
+        assert_eq!(profile.profile_type(), ProfileType::AppStore);
+        profile.provisioned_devices = vec!["a".into()];
+        assert_eq!(profile.profile_type(), ProfileType::AdHoc);
+        profile.get_task_allow = true;
+        assert_eq!(profile.profile_type(), ProfileType::Development);
+        profile.provisioned_devices.clear();
+        profile.provisions_all_devices = true;
+        assert_eq!(profile.profile_type(), ProfileType::Enterprise);
+        for profile_type in ProfileType::ALL {
+            assert_eq!(
+                profile_type.to_string().parse::<ProfileType>(),
+                Ok(*profile_type)
+            );
+        }
+        assert!("distribution".parse::<ProfileType>().is_err());
+    }
+
+    #[test]
+    fn diff_changed_fields() {
+        let old = Info::empty();
+        let mut new = Info::empty();
+        new.name = "Profile".into();
+        new.provisioned_devices = vec!["a".into(), "b".into()];
+        new.get_task_allow = true;
+        let changed: Vec<_> = old
+            .diff(&new)
+            .into_iter()
+            .filter(FieldDiff::is_changed)
+            .collect();
+        assert_eq!(
+            changed,
+            vec![
+                FieldDiff {
+                    field: "Name",
+                    old: "".into(),
+                    new: "Profile".into(),
+                },
+                FieldDiff {
+                    field: "ProvisionedDevices",
+                    old: "".into(),
+                    new: "a, b".into(),
+                },
+                FieldDiff {
+                    field: "Entitlements.get-task-allow",
+                    old: "false".into(),
+                    new: "true".into(),
+                },
+            ]
+        );
+        assert!(!old.diff(&old).iter().any(FieldDiff::is_changed));
+    }
+
+    #[test]
+    fn valid_uuid() {
+        let mut profile = Info::empty();
+        assert!(!profile.has_valid_uuid());
+        profile.uuid = "FBCDEF0A-af78-4a11".into();
+        assert!(profile.has_valid_uuid());
+        for uuid in ["../evil", "a/b", "ab.c", "abcg"] {
+            profile.uuid = uuid.into();
+            assert!(!profile.has_valid_uuid());
+        }
+    }
+
+    #[test]
+    fn diff_rotated_certificate() {
+        let mut old = Info::empty();
+        old.developer_certificates = vec![b"first".to_vec(), b"second".to_vec()];
+        let mut new = old.clone();
+        assert!(!old.diff(&new).iter().any(FieldDiff::is_changed));
+        new.developer_certificates[1] = b"third".to_vec();
+        let changed: Vec<_> = old
+            .diff(&new)
+            .into_iter()
+            .filter(FieldDiff::is_changed)
+            .map(|diff| diff.field)
+            .collect();
+        assert_eq!(changed, ["DeveloperCertificates"]);
+    }
 }
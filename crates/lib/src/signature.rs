@@ -0,0 +1,399 @@
+//! Verification of the CMS signature of provisioning profiles.
+//!
+//! A mobileprovision file is a CMS `SignedData` envelope (usually BER encoded)
+//! that wraps the plist content. The signature is considered valid if the
+//! content digest matches, the signer certificate signs the content, the
+//! signer certificate is issued by one of [`APPLE_WWDR_CERT`] authorities and
+//! the chain of certificates ends with the Apple Root CA certificate pinned by
+//! [`APPLE_ROOT_CA_SHA256`].
+
+use crate::{Error, Result};
+use cms::cert::x509::certificate::Certificate;
+use cms::cert::x509::name::Name;
+use cms::cert::x509::spki::ObjectIdentifier;
+use cms::cert::CertificateChoices;
+use cms::content_info::ContentInfo;
+use cms::signed_data::{SignedData, SignerIdentifier, SignerInfo};
+use der::{Decode, Encode};
+use rsa::pkcs8::DecodePublicKey;
+use rsa::{Pkcs1v15Sign, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+
+/// Common names of Apple certificate authorities that issue certificates for
+/// signing of provisioning profiles.
+///
+/// The Worldwide Developer Relations authority isn't listed because it issues
+/// certificates to developers as well.
+pub const APPLE_WWDR_CERT: &[&str] = &["Apple iPhone Certification Authority"];
+
+/// A SHA-256 fingerprint of the DER encoded Apple Root CA certificate, see
+/// <https://www.apple.com/certificateauthority/>.
+pub const APPLE_ROOT_CA_SHA256: [u8; 32] = [
+    0xb0, 0xb1, 0x73, 0x0e, 0xcb, 0xc7, 0xff, 0x45, 0x05, 0x14, 0x2c, 0x49, 0xf1, 0x29, 0x5e, 0x6e,
+    0xda, 0x6b, 0xca, 0xed, 0x7e, 0x2c, 0x68, 0xc5, 0xbe, 0x91, 0xb5, 0xa1, 0x10, 0x01, 0xf0, 0x24,
+];
+
+/// The maximum number of certificates in a chain from a signer to a root.
+const MAX_CHAIN_LEN: usize = 8;
+
+const OID_SIGNED_DATA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.7.2");
+const OID_MESSAGE_DIGEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.4");
+const OID_COMMON_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.4.3");
+const OID_SHA1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.3.14.3.2.26");
+const OID_SHA256: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.16.840.1.101.3.4.2.1");
+const OID_SHA1_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.5");
+const OID_SHA256_WITH_RSA: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.1.11");
+
+/// Returns `true` if the CMS signature of a provisioning profile `data` is valid
+/// and issued by Apple.
+///
+/// # Errors
+/// This function will return an error if the `data` is not a CMS envelope.
+pub fn verify(data: &[u8]) -> Result<bool> {
+    verify_with_roots(data, &[APPLE_ROOT_CA_SHA256], APPLE_WWDR_CERT)
+}
+
+/// Returns `true` if the CMS signature of a `data` is valid, the signer
+/// certificate is issued by one of `authorities` (common names) and its chain
+/// ends with a certificate whose SHA-256 fingerprint is one of `roots`.
+///
+/// # Errors
+/// This function will return an error if the `data` is not a CMS envelope.
+pub fn verify_with_roots(data: &[u8], roots: &[[u8; 32]], authorities: &[&str]) -> Result<bool> {
+    let der = ber_to_der(data).ok_or_else(|| Error::Own("Couldn't parse CMS envelope.".into()))?;
+    let content_info = ContentInfo::from_der(&der).map_err(external)?;
+    if content_info.content_type != OID_SIGNED_DATA {
+        return Err(Error::Own(
+            "CMS envelope doesn't contain signed data.".into(),
+        ));
+    }
+    let signed_data: SignedData = content_info.content.decode_as().map_err(external)?;
+    let content = match &signed_data.encap_content_info.econtent {
+        Some(content) => content.value(),
+        None => return Ok(false),
+    };
+    let certificates = signed_data
+        .certificates
+        .iter()
+        .flat_map(|set| set.0.iter())
+        .filter_map(|choice| match choice {
+            CertificateChoices::Certificate(certificate) => Some(certificate),
+            CertificateChoices::Other(_) => None,
+        })
+        .collect::<Vec<_>>();
+    for signer_info in signed_data.signer_infos.0.iter() {
+        let Some(signer) = find_signer(&certificates, signer_info) else {
+            return Ok(false);
+        };
+        if !verify_signer_info(signer_info, signer, content)? {
+            return Ok(false);
+        }
+        if !is_issued_by(signer, authorities) || !chains_to_root(signer, &certificates, roots)? {
+            return Ok(false);
+        }
+    }
+    Ok(!signed_data.signer_infos.0.is_empty())
+}
+
+fn external(e: der::Error) -> Error {
+    Error::External(Box::new(e))
+}
+
+fn find_signer<'a>(
+    certificates: &[&'a Certificate],
+    signer_info: &SignerInfo,
+) -> Option<&'a Certificate> {
+    match &signer_info.sid {
+        SignerIdentifier::IssuerAndSerialNumber(id) => certificates
+            .iter()
+            .find(|certificate| {
+                certificate.tbs_certificate.issuer == id.issuer
+                    && certificate.tbs_certificate.serial_number == id.serial_number
+            })
+            .copied(),
+        SignerIdentifier::SubjectKeyIdentifier(_) => None,
+    }
+}
+
+/// Verifies that the `content` is signed by the `signer` certificate.
+fn verify_signer_info(
+    signer_info: &SignerInfo,
+    signer: &Certificate,
+    content: &[u8],
+) -> Result<bool> {
+    let digest_alg = signer_info.digest_alg.oid;
+    let Some(content_digest) = digest(digest_alg, content) else {
+        return Ok(false);
+    };
+    let signed = match &signer_info.signed_attrs {
+        Some(attrs) => {
+            let message_digest = attrs
+                .iter()
+                .find(|attr| attr.oid == OID_MESSAGE_DIGEST)
+                .and_then(|attr| attr.values.get(0))
+                .and_then(|value| value.decode_as::<der::asn1::OctetString>().ok());
+            match message_digest {
+                Some(message_digest) if message_digest.as_bytes() == content_digest => {}
+                _ => return Ok(false),
+            }
+            attrs.to_der().map_err(external)?
+        }
+        None => content.to_owned(),
+    };
+    verify_rsa(
+        signer,
+        digest_alg,
+        &signed,
+        signer_info.signature.as_bytes(),
+    )
+}
+
+/// Returns `true` if the common name of the `certificate` issuer is one of
+/// `authorities`.
+fn is_issued_by(certificate: &Certificate, authorities: &[&str]) -> bool {
+    let issuer_name = common_name(&certificate.tbs_certificate.issuer);
+    authorities
+        .iter()
+        .any(|authority| issuer_name.as_deref() == Some(authority))
+}
+
+/// Returns `true` if the `certificate` is signed by a chain of `certificates`
+/// that ends with a certificate whose SHA-256 fingerprint is one of `roots`.
+fn chains_to_root(
+    certificate: &Certificate,
+    certificates: &[&Certificate],
+    roots: &[[u8; 32]],
+) -> Result<bool> {
+    let mut certificate = certificate;
+    for _ in 0..MAX_CHAIN_LEN {
+        let fingerprint = Sha256::digest(certificate.to_der().map_err(external)?);
+        if roots.iter().any(|root| root[..] == fingerprint[..]) {
+            return Ok(true);
+        }
+        let mut issuer = None;
+        for candidate in certificates {
+            if candidate.tbs_certificate.subject == certificate.tbs_certificate.issuer
+                && is_signed_by(certificate, candidate)?
+            {
+                issuer = Some(*candidate);
+                break;
+            }
+        }
+        let Some(issuer) = issuer else {
+            return Ok(false);
+        };
+        certificate = issuer;
+    }
+    Ok(false)
+}
+
+/// Returns `true` if the `certificate` is signed by the `issuer` certificate.
+fn is_signed_by(certificate: &Certificate, issuer: &Certificate) -> Result<bool> {
+    let digest_alg = match certificate.signature_algorithm.oid {
+        OID_SHA1_WITH_RSA => OID_SHA1,
+        OID_SHA256_WITH_RSA => OID_SHA256,
+        _ => return Ok(false),
+    };
+    let Some(signature) = certificate.signature.as_bytes() else {
+        return Ok(false);
+    };
+    let tbs = certificate.tbs_certificate.to_der().map_err(external)?;
+    verify_rsa(issuer, digest_alg, &tbs, signature)
+}
+
+fn verify_rsa(
+    certificate: &Certificate,
+    digest_alg: ObjectIdentifier,
+    message: &[u8],
+    signature: &[u8],
+) -> Result<bool> {
+    let spki = certificate
+        .tbs_certificate
+        .subject_public_key_info
+        .to_der()
+        .map_err(external)?;
+    let Ok(key) = RsaPublicKey::from_public_key_der(&spki) else {
+        return Ok(false);
+    };
+    let (scheme, hashed) = match digest_alg {
+        OID_SHA1 => (Pkcs1v15Sign::new::<Sha1>(), Sha1::digest(message).to_vec()),
+        OID_SHA256 => (
+            Pkcs1v15Sign::new::<Sha256>(),
+            Sha256::digest(message).to_vec(),
+        ),
+        _ => return Ok(false),
+    };
+    Ok(key.verify(scheme, &hashed, signature).is_ok())
+}
+
+fn digest(digest_alg: ObjectIdentifier, data: &[u8]) -> Option<Vec<u8>> {
+    match digest_alg {
+        OID_SHA1 => Some(Sha1::digest(data).to_vec()),
+        OID_SHA256 => Some(Sha256::digest(data).to_vec()),
+        _ => None,
+    }
+}
+
+fn common_name(name: &Name) -> Option<String> {
+    name.0
+        .iter()
+        .flat_map(|rdn| rdn.0.iter())
+        .find(|attr| attr.oid == OID_COMMON_NAME)
+        .map(|attr| attr.to_string())
+        .and_then(|attr| attr.strip_prefix("CN=").map(str::to_owned))
+}
+
+/// Converts BER encoded `data` to DER encoding, so it can be decoded by the
+/// `der` crate.
+///
+/// Only indefinite lengths and constructed octet strings are converted, that's
+/// enough for CMS envelopes of provisioning profiles.
+fn ber_to_der(data: &[u8]) -> Option<Vec<u8>> {
+    let (element, len) = Element::parse(data, 0)?;
+    if len != data.len() {
+        return None;
+    }
+    let mut der = Vec::with_capacity(data.len());
+    element.encode(&mut der);
+    Some(der)
+}
+
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_CONSTRUCTED_OCTET_STRING: u8 = 0x24;
+const CONSTRUCTED: u8 = 0x20;
+/// The maximum nesting of elements, so crafted data can't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+struct Element {
+    tag: Vec<u8>,
+    content: Vec<u8>,
+}
+
+impl Element {
+    /// Parses an element nested at `depth` and returns it along with the number
+    /// of consumed bytes.
+    fn parse(data: &[u8], depth: usize) -> Option<(Self, usize)> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        let mut i = 1;
+        if data.first()? & 0x1f == 0x1f {
+            while data.get(i)? & 0x80 != 0 {
+                i += 1;
+            }
+            i += 1;
+        }
+        let tag = data[..i].to_vec();
+        let constructed = tag[0] & CONSTRUCTED != 0;
+        let length_byte = *data.get(i)?;
+        i += 1;
+        let length = match length_byte {
+            0x80 => None,
+            byte if byte < 0x80 => Some(byte as usize),
+            byte => {
+                let count = (byte & 0x7f) as usize;
+                let bytes = data.get(i..i.checked_add(count)?)?;
+                i += count;
+                Some(bytes.iter().try_fold(0usize, |acc, b| {
+                    acc.checked_mul(256)?.checked_add(*b as usize)
+                })?)
+            }
+        };
+        if !constructed {
+            let length = length?;
+            let end = i.checked_add(length)?;
+            let content = data.get(i..end)?.to_vec();
+            return Some((Self { tag, content }, end));
+        }
+        let mut children = Vec::new();
+        let end = match length {
+            Some(length) => {
+                let end = i.checked_add(length)?;
+                let content = data.get(i..end)?;
+                let mut offset = 0;
+                while offset < content.len() {
+                    let (child, len) = Self::parse(&content[offset..], depth + 1)?;
+                    children.push(child);
+                    offset += len;
+                }
+                end
+            }
+            None => loop {
+                if data.get(i..i.checked_add(2)?)? == [0, 0] {
+                    break i + 2;
+                }
+                let (child, len) = Self::parse(&data[i..], depth + 1)?;
+                children.push(child);
+                i += len;
+            },
+        };
+        let mut content = Vec::new();
+        if tag == [TAG_CONSTRUCTED_OCTET_STRING] {
+            for child in children {
+                content.extend(child.content);
+            }
+            return Some((
+                Self {
+                    tag: vec![TAG_OCTET_STRING],
+                    content,
+                },
+                end,
+            ));
+        }
+        for child in children {
+            child.encode(&mut content);
+        }
+        Some((Self { tag, content }, end))
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend(&self.tag);
+        let len = self.content.len();
+        if len < 0x80 {
+            out.push(len as u8);
+        } else {
+            let bytes = len.to_be_bytes();
+            let skip = bytes.iter().take_while(|b| **b == 0).count();
+            out.push(0x80 | (bytes.len() - skip) as u8);
+            out.extend(&bytes[skip..]);
+        }
+        out.extend(&self.content);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_indefinite_length_to_definite() {
+        let ber = [0x30, 0x80, 0x02, 0x01, 0x05, 0x00, 0x00];
+        assert_eq!(ber_to_der(&ber), Some(vec![0x30, 0x03, 0x02, 0x01, 0x05]));
+    }
+
+    #[test]
+    fn joins_constructed_octet_string() {
+        let ber = [0x24, 0x80, 0x04, 0x01, 0x61, 0x04, 0x01, 0x62, 0x00, 0x00];
+        assert_eq!(ber_to_der(&ber), Some(vec![0x04, 0x02, 0x61, 0x62]));
+    }
+
+    #[test]
+    fn rejects_truncated_data() {
+        assert_eq!(ber_to_der(&[0x30, 0x80, 0x02, 0x01]), None);
+    }
+
+    #[test]
+    fn rejects_deeply_nested_data() {
+        let mut ber = [0x30, 0x80].repeat(MAX_DEPTH + 2);
+        ber.extend([0x00, 0x00].repeat(MAX_DEPTH + 2));
+        assert_eq!(ber_to_der(&ber), None);
+    }
+
+    #[test]
+    fn rejects_overflowing_length() {
+        let mut ber = vec![0x04, 0x88];
+        ber.extend(usize::MAX.to_be_bytes());
+        assert_eq!(ber_to_der(&ber), None);
+    }
+}
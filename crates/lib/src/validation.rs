@@ -0,0 +1,72 @@
+//! Validation of provisioning profiles.
+
+use crate::profile::{self, Info, Profile, ProfileRef};
+use crate::Result;
+use std::fmt;
+
+/// Represents a problem found during validation of a provisioning profile.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ValidationError {
+    /// The profile is not signed by Apple or has been tampered.
+    InvalidSignature,
+    /// The profile expired more than 5 years before its creation or more than 5
+    /// years ago.
+    AncientExpiry,
+    /// The team identifier is not 10 alphanumeric characters.
+    InvalidTeamId(String),
+    /// The version of the profile format is not `1`.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidSignature => write!(f, "Invalid CMS signature"),
+            Self::AncientExpiry => write!(f, "Expiration date is implausibly old"),
+            Self::InvalidTeamId(id) => write!(f, "Invalid team identifier '{}'", id),
+            Self::UnsupportedVersion(version) => write!(f, "Unsupported version {}", version),
+        }
+    }
+}
+
+/// Validates a provisioning profile and returns all found problems.
+///
+/// Plausibility of the expiration date is checked only with the `strict-validation`
+/// feature.
+///
+/// # Errors
+/// This function will return an error if the profile cannot be read.
+pub fn validate_profile(profile: &Profile) -> Result<Vec<ValidationError>> {
+    Ok(validate(
+        &profile.info,
+        profile.verify_signature().unwrap_or(false),
+    ))
+}
+
+/// Validates a provisioning profile and returns all found problems.
+///
+/// The same as [`validate_profile`] but doesn't read the file again.
+pub fn validate_profile_ref(profile: &ProfileRef) -> Vec<ValidationError> {
+    validate(&profile.info, profile.verify_signature().unwrap_or(false))
+}
+
+#[cfg_attr(not(feature = "strict-validation"), allow(unused_variables))]
+fn validate(info: &Info, is_signed: bool) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    if !is_signed {
+        errors.push(ValidationError::InvalidSignature);
+    }
+    if !profile::is_valid_team_id(info.team_identifier.as_str()) {
+        errors.push(ValidationError::InvalidTeamId(
+            info.team_identifier.to_string(),
+        ));
+    }
+    if info.version() != 1 {
+        errors.push(ValidationError::UnsupportedVersion(info.version()));
+    }
+    #[cfg(feature = "strict-validation")]
+    if let Err(error) = info.sanity_check() {
+        errors.push(error);
+    }
+    errors
+}
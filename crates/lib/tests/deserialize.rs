@@ -1,4 +1,5 @@
-use mprovision::profile::Info;
+use mprovision::error::Error;
+use mprovision::profile::{Info, InfoError, MinimalInfo, ParseWarning, Profile, TeamId};
 use std::time::{Duration, SystemTime};
 
 fn time(secs: u64) -> SystemTime {
@@ -11,12 +12,312 @@ fn time(secs: u64) -> SystemTime {
 fn deserialize() {
     let data = std::fs::read("tests/test.xml").unwrap();
     let info = Info::from_xml_data(&data).unwrap();
-    let expected = Info {
-        uuid: "fbcdefgl-af78-hal1-lgl1-87jl897lja8e".to_owned(),
+    assert_eq!(info, expected());
+}
+
+#[test]
+fn deserialize_minimal() {
+    let data = std::fs::read("tests/test.xml").unwrap();
+    let expected = expected();
+    assert_eq!(
+        Info::from_xml_data_minimal(&data).unwrap(),
+        MinimalInfo {
+            uuid: expected.uuid,
+            name: expected.name,
+            app_identifier: expected.app_identifier,
+            expiration_date: expected.expiration_date,
+        }
+    );
+}
+
+#[test]
+fn deserialize_using_free_functions() {
+    let data = std::fs::read("tests/test.xml").unwrap();
+    assert_eq!(mprovision::info_from_xml_data(&data).unwrap(), expected());
+    let profile = mprovision::profile_from_file("tests/test.xml".as_ref()).unwrap();
+    assert_eq!(profile.info, expected());
+}
+
+#[test]
+fn deserialize_shown_xml() {
+    let xml = mprovision::show("tests/test.xml".as_ref()).unwrap();
+    let info = Info::from_clean_xml(xml.as_bytes()).unwrap();
+    assert_eq!(info, expected());
+}
+
+#[test]
+fn deserialize_dates_with_timezone_offset() {
+    for creation_date in [
+        "<date>2024-01-15T10:30:00+05:30</date>",
+        "<string>2024-01-15T10:30:00+05:30</string>",
+    ] {
+        let data = std::fs::read_to_string("tests/test.xml")
+            .unwrap()
+            .replace("<date>2019-07-12T10:20:02Z</date>", creation_date);
+        let info = Info::from_xml_data(data.as_bytes()).unwrap();
+        assert_eq!(info.creation_date, time(1705294800));
+    }
+}
+
+#[test]
+fn deserialize_dates_as_strings() {
+    let data = std::fs::read_to_string("tests/test.xml")
+        .unwrap()
+        .replace("<date>", "<string>")
+        .replace("</date>", "</string>");
+    assert!(!data.contains("<date>"));
+    assert_eq!(Info::from_xml_data(data.as_bytes()).unwrap(), expected());
+}
+
+#[test]
+fn deserialize_duplicated_devices() {
+    let data = std::fs::read_to_string("tests/test.xml").unwrap().replace(
+        "<string>ahhboajfhajdfhvajodhfbknadfljlkgjlajlkal</string>",
+        "<string>AAAA</string><string>BBBB</string><string>AAAA</string>",
+    );
+    let info = Info::from_xml_data(data.as_bytes()).unwrap();
+    assert_eq!(info.provisioned_devices, vec!["AAAA", "BBBB"]);
+    assert_eq!(info.provisioned_device_count(), Some(2));
+}
+
+#[test]
+fn deserialize_app_identifier_array() {
+    let data = std::fs::read_to_string("tests/test.xml").unwrap().replace(
+        "<string>1234567890.com.testapp</string>",
+        "<array><string>1234567890.com.testapp</string><string>1234567890.com.other</string></array>",
+    );
+    assert_eq!(Info::from_xml_data(data.as_bytes()).unwrap(), expected());
+    let data = std::fs::read_to_string("tests/test.xml")
+        .unwrap()
+        .replace("<string>1234567890.com.testapp</string>", "<array></array>");
+    assert!(Info::from_xml_data(data.as_bytes()).is_err());
+    assert!(Info::from_xml_data_opt(data.as_bytes()).is_none());
+}
+
+#[test]
+fn deserialize_crlf_line_endings() {
+    let data = std::fs::read_to_string("tests/test.xml")
+        .unwrap()
+        .replace('\n', "\r\n");
+    assert_eq!(Info::from_xml_data(data.as_bytes()).unwrap(), expected());
+    assert!(Info::from_xml_data_minimal(data.as_bytes()).is_some());
+}
+
+#[test]
+fn deserialize_invalid_date_with_reason() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("1.mobileprovision");
+    let data = std::fs::read_to_string("tests/test.xml").unwrap().replace(
+        "<date>2019-07-12T10:20:02Z</date>",
+        "<date>yesterday</date>",
+    );
+    std::fs::write(&path, data).unwrap();
+    let err = Profile::from_file(&path).unwrap_err();
+    assert!(matches!(&err, Error::Parse { path: err_path, .. } if *err_path == path));
+    assert!(err.to_string().contains("1.mobileprovision"));
+    assert!(matches!(
+        &err,
+        Error::Parse {
+            source: InfoError::PlistParseError(_),
+            ..
+        }
+    ));
+}
+
+#[test]
+fn deserialize_team_identifier_from_app_identifier() {
+    let data = std::fs::read_to_string("tests/test.xml")
+        .unwrap()
+        .replace(
+            "<key>TeamIdentifier</key>\n\t<array>\n\t\t<string>1234567890</string>\n\t</array>",
+            "",
+        )
+        .replace("1234567890.com.testapp", "ABCDE12345.com.example.app");
+    assert!(!data.contains("TeamIdentifier"));
+    let info = Info::from_xml_data(data.as_bytes()).unwrap();
+    assert_eq!(info.team_identifier, TeamId("ABCDE12345".into()));
+}
+
+#[test]
+fn deserialize_without_provisioned_devices() {
+    let data = std::fs::read_to_string("tests/test.xml").unwrap().replace(
+        "<key>ProvisionedDevices</key>\n\t<array>\n\t\t<string>ahhboajfhajdfhvajodhfbknadfljlkgjlajlkal</string>\n\t</array>",
+        "",
+    );
+    assert!(!data.contains("ProvisionedDevices"));
+    let info = Info::from_xml_data(data.as_bytes()).unwrap();
+    assert!(info.provisioned_devices.is_empty());
+}
+
+#[test]
+fn deserialize_platforms() {
+    let data = std::fs::read_to_string("tests/test.xml").unwrap();
+    for (platform, name) in [
+        ("iOS", "ios"),
+        ("OSX", "macos"),
+        ("tvOS", "tvos"),
+        ("watchOS", "watchos"),
+    ] {
+        let info = Info::from_xml_data(
+            data.replace(
+                "<string>iOS</string>",
+                &format!("<string>{}</string>", platform),
+            )
+            .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(info.platforms, [platform]);
+        assert!(info.is_for_platform(name));
+    }
+    let info = Info::from_xml_data(
+        data.replace("<key>Platform</key>", "<key>Platforms</key>")
+            .as_bytes(),
+    )
+    .unwrap();
+    assert!(info.platforms.is_empty());
+    assert!(!info.is_for_platform("ios"));
+}
+
+#[test]
+fn deserialize_version() {
+    let data = std::fs::read_to_string("tests/test.xml").unwrap();
+    let info = Info::from_xml_data(
+        data.replace(
+            "<key>Version</key>\n\t<integer>1</integer>",
+            "<key>Version</key>\n\t<integer>2</integer>",
+        )
+        .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(info.version(), 2);
+    let info = Info::from_xml_data(
+        data.replace("<key>Version</key>\n\t<integer>1</integer>", "")
+            .as_bytes(),
+    )
+    .unwrap();
+    assert_eq!(info.version(), 1);
+}
+
+#[test]
+fn deserialize_partial() {
+    let data = std::fs::read_to_string("tests/test.xml").unwrap();
+    let partial = Info::from_xml_data_partial(data.as_bytes()).unwrap();
+    assert_eq!(partial.info, expected());
+    assert!(partial.warnings.is_empty());
+
+    let data = data
+        .replace(
+            "<date>2020-07-11T10:20:02Z</date>",
+            "<string>next year</string>",
+        )
+        .replace(
+            "<key>TimeToLive</key>",
+            "<key>TimeToLive</key><true/><key>Unused</key>",
+        );
+    assert!(Info::from_xml_data(data.as_bytes()).is_err());
+    let partial = Info::from_xml_data_partial(data.as_bytes()).unwrap();
+    assert_eq!(
+        partial.warnings,
+        vec![
+            ParseWarning::InvalidField("TimeToLive"),
+            ParseWarning::InvalidField("ExpirationDate"),
+        ]
+    );
+    assert_eq!(partial.info.uuid, expected().uuid);
+    assert_eq!(partial.info.expiration_date, SystemTime::UNIX_EPOCH);
+    assert_eq!(partial.info.time_to_live, 0);
+
+    let data = data.replace("<key>UUID</key>", "<key>Unused</key>");
+    assert!(Info::from_xml_data_partial(data.as_bytes()).is_err());
+}
+
+#[test]
+fn plist_dict_round_trip() {
+    let data = std::fs::read("tests/test.xml").unwrap();
+    let info = Info::from_xml_data(&data).unwrap();
+    let mut xml = Vec::new();
+    plist::Value::Dictionary(info.to_plist_dict())
+        .to_writer_xml(&mut xml)
+        .unwrap();
+    assert_eq!(Info::from_xml_data(&xml).unwrap(), info);
+}
+
+#[test]
+fn deserialize_real_format() {
+    let data = std::fs::read("tests/real_format.xml").unwrap();
+    let info = Info::from_xml_data(&data).unwrap();
+    assert_eq!(info.uuid, "5a8b3c1d-7e2f-4a6b-9c0d-1e2f3a4b5c6d");
+    assert_eq!(
+        info.name,
+        "iOS Team Provisioning Profile: com.example.MyApp"
+    );
+    assert_eq!(info.app_identifier.as_str(), "A1B2C3D4E5.com.example.MyApp");
+    assert_eq!(info.team_name, "Example Corp");
+    assert_eq!(info.team_identifier, TeamId("A1B2C3D4E5".into()));
+    assert_eq!(info.creation_date, time(1709284530));
+    assert_eq!(info.expiration_date, time(1740820530));
+    assert_eq!(info.developer_certificates.len(), 2);
+    assert!(info
+        .developer_certificates
+        .iter()
+        .all(|der| der.starts_with(&[0x30, 0x82])));
+    assert_eq!(
+        info.provisioned_devices,
+        vec![
+            "00008030-001A2D3E0C12802E".to_owned(),
+            "4c2f1a9e0b7d3e5f6a8c9b0d1e2f3a4b5c6d7e8f".to_owned(),
+        ]
+    );
+    assert!(!info.provisions_all_devices);
+    assert_eq!(info.time_to_live, 365);
+    assert_eq!(info.version, 1);
+    assert_eq!(info.platforms, ["iOS", "xrOS", "visionOS"]);
+}
+
+fn expected() -> Info {
+    Info {
+        uuid: "fbcdef0a-af78-4a11-b1f1-87cb897bfa8e".to_owned(),
         name: "TestApp iOS Development".to_owned(),
-        app_identifier: "1234567890.com.testapp".to_owned(),
+        app_id_name: "TestApp".into(),
+        app_identifier: "1234567890.com.testapp".into(),
+        team_name: "My Company, Inc".into(),
+        team_identifier: TeamId("1234567890".into()),
         creation_date: time(1562926802),
         expiration_date: time(1594462802),
-    };
-    assert_eq!(info, expected);
+        developer_certificates: Vec::new(),
+        provisioned_devices: vec!["ahhboajfhajdfhvajodhfbknadfljlkgjlajlkal".to_owned()],
+        provisions_all_devices: false,
+        get_task_allow: true,
+        platforms: vec!["iOS".to_owned()],
+        time_to_live: 365,
+        version: 1,
+    }
+}
+
+#[test]
+fn deserialize_no_plist_found() {
+    assert!(matches!(
+        Info::from_xml_data(b"garbage"),
+        Err(InfoError::NoPlistFound)
+    ));
+}
+
+#[test]
+fn deserialize_plist_parse_error() {
+    let data = "<?xml version=\"1.0\"?><plist version=\"1.0\"><dict><key>UUID</key></dict></plist>";
+    assert!(matches!(
+        Info::from_xml_data(data.as_bytes()),
+        Err(InfoError::PlistParseError(_))
+    ));
+}
+
+#[test]
+fn deserialize_missing_app_identifier() {
+    let data = std::fs::read_to_string("tests/test.xml")
+        .unwrap()
+        .replace("<string>1234567890.com.testapp</string>", "<array></array>");
+    assert!(matches!(
+        Info::from_xml_data(data.as_bytes()),
+        Err(InfoError::MissingField("application-identifier"))
+    ));
 }
@@ -0,0 +1,61 @@
+use mprovision::profile::{Profile, ProfileRef};
+use mprovision::{signature, validation};
+
+const AUTHORITY: &str = "mprovision Test CA";
+/// A SHA-256 fingerprint of the "mprovision Test CA" certificate.
+const ROOT: [u8; 32] = [
+    0x2c, 0xde, 0xa0, 0x65, 0xe9, 0x43, 0x95, 0x62, 0xea, 0x15, 0x3e, 0x3f, 0x87, 0x02, 0x03, 0xde,
+    0x64, 0x15, 0x86, 0x8d, 0xc1, 0x7c, 0x25, 0x97, 0x8c, 0xd7, 0x41, 0x0a, 0xe8, 0x7e, 0xd9, 0x3a,
+];
+
+#[test]
+fn verify_signed_profile() {
+    let data = std::fs::read("tests/signed.mobileprovision").unwrap();
+    assert!(signature::verify_with_roots(&data, &[ROOT], &[AUTHORITY]).unwrap());
+}
+
+#[test]
+fn verify_profile_with_untrusted_root() {
+    let data = std::fs::read("tests/signed.mobileprovision").unwrap();
+    assert!(!signature::verify_with_roots(&data, &[[0; 32]], &[AUTHORITY]).unwrap());
+    assert!(!signature::verify_with_roots(&data, &[ROOT], &["Other CA"]).unwrap());
+}
+
+#[test]
+fn verify_profile_not_signed_by_apple() {
+    let data = std::fs::read("tests/signed.mobileprovision").unwrap();
+    assert!(!signature::verify(&data).unwrap());
+    let profile = Profile::from_file("tests/signed.mobileprovision".as_ref()).unwrap();
+    assert!(!profile.verify_signature().unwrap());
+}
+
+#[test]
+fn verify_profile_ref_without_reading_file_again() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let path = temp_dir.path().join("signed.mobileprovision");
+    std::fs::copy("tests/signed.mobileprovision", &path).unwrap();
+    let profile = ProfileRef::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        profile.raw_bytes(),
+        std::fs::read("tests/signed.mobileprovision").unwrap()
+    );
+    assert!(!profile.verify_signature().unwrap());
+    assert!(validation::validate_profile_ref(&profile)
+        .contains(&validation::ValidationError::InvalidSignature));
+    assert_eq!(Profile::from(profile.clone()).info, profile.info);
+}
+
+#[test]
+fn verify_tampered_profile() {
+    let mut data = std::fs::read("tests/signed.mobileprovision").unwrap();
+    let i = memchr::memmem::find(&data, b"TestApp").unwrap();
+    data[i] = b'B';
+    assert!(!signature::verify_with_roots(&data, &[ROOT], &[AUTHORITY]).unwrap());
+}
+
+#[test]
+fn verify_plain_xml_should_err() {
+    let data = std::fs::read("tests/test.xml").unwrap();
+    assert!(signature::verify(&data).is_err());
+}